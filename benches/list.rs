@@ -0,0 +1,155 @@
+//! Compares `DoublyLinkedList` and `ArenaList` against the
+//! `Rc<RefCell<Node>>` design `DoublyLinkedList` replaced, which is kept
+//! below as `rc_list::RcList`.
+//!
+//! ```bash
+//! cargo bench --bench list
+//! ```
+
+use std::hint::black_box;
+
+use criterion::{BenchmarkId, Criterion, criterion_group, criterion_main};
+use dll_rs::{ArenaList, DoublyLinkedList};
+
+/// The original `Link<T>`/`WeakLink<T>` backing, reduced to the operations
+/// that are benchmarked.
+mod rc_list {
+    use std::cell::RefCell;
+    use std::rc::{Rc, Weak};
+
+    type Link<T> = Option<Rc<RefCell<Node<T>>>>;
+    type WeakLink<T> = Option<Weak<RefCell<Node<T>>>>;
+
+    struct Node<T> {
+        val: T,
+        next: Link<T>,
+        prev: WeakLink<T>,
+    }
+
+    pub struct RcList<T> {
+        head: Link<T>,
+        tail: Link<T>,
+    }
+
+    impl<T> RcList<T> {
+        pub fn new() -> Self {
+            RcList {
+                head: None,
+                tail: None,
+            }
+        }
+
+        pub fn push_back(&mut self, val: T) {
+            let new_tail = Rc::new(RefCell::new(Node {
+                val,
+                next: None,
+                prev: None,
+            }));
+            match self.tail.take() {
+                Some(old_tail) => {
+                    old_tail.borrow_mut().next = Some(Rc::clone(&new_tail));
+                    new_tail.borrow_mut().prev = Some(Rc::downgrade(&old_tail));
+                    self.tail = Some(new_tail);
+                }
+                None => {
+                    self.head = Some(Rc::clone(&new_tail));
+                    self.tail = Some(new_tail);
+                }
+            }
+        }
+
+        pub fn pop_front(&mut self) -> Option<T> {
+            self.head.take().map(|old_head| {
+                match old_head.borrow_mut().next.take() {
+                    Some(new_head) => {
+                        new_head.borrow_mut().prev.take();
+                        self.head = Some(new_head);
+                    }
+                    None => {
+                        self.tail.take();
+                    }
+                }
+                Rc::try_unwrap(old_head).ok().unwrap().into_inner().val
+            })
+        }
+
+        /// Walks the list through `borrow()`, as any safe traversal had to.
+        pub fn sum(&self) -> u64
+        where
+            T: Copy + Into<u64>,
+        {
+            let mut sum = 0;
+            let mut current = self.head.clone();
+            while let Some(node) = current {
+                let node = node.borrow();
+                sum += node.val.into();
+                current = node.next.clone();
+            }
+            sum
+        }
+    }
+
+    impl<T> Drop for RcList<T> {
+        fn drop(&mut self) {
+            while self.pop_front().is_some() {}
+        }
+    }
+}
+
+use rc_list::RcList;
+
+const SIZES: [u32; 2] = [1_000, 100_000];
+
+fn push_pop(c: &mut Criterion) {
+    let mut group = c.benchmark_group("push_back_then_pop_front");
+    for size in SIZES {
+        group.bench_with_input(BenchmarkId::new("NonNull", size), &size, |b, &size| {
+            b.iter(|| {
+                let mut list = DoublyLinkedList::new();
+                (0..size).for_each(|i| list.push_back(black_box(i)));
+                while let Some(val) = list.pop_front() {
+                    black_box(val);
+                }
+            })
+        });
+        group.bench_with_input(BenchmarkId::new("Arena", size), &size, |b, &size| {
+            b.iter(|| {
+                let mut list = ArenaList::new();
+                (0..size).for_each(|i| list.push_back(black_box(i)));
+                while let Some(val) = list.pop_front() {
+                    black_box(val);
+                }
+            })
+        });
+        group.bench_with_input(BenchmarkId::new("RcRefCell", size), &size, |b, &size| {
+            b.iter(|| {
+                let mut list = RcList::new();
+                (0..size).for_each(|i| list.push_back(black_box(i)));
+                while let Some(val) = list.pop_front() {
+                    black_box(val);
+                }
+            })
+        });
+    }
+    group.finish();
+}
+
+fn traverse(c: &mut Criterion) {
+    let mut group = c.benchmark_group("traverse");
+    for size in SIZES {
+        let list: DoublyLinkedList<u32> = (0..size).collect();
+        group.bench_with_input(BenchmarkId::new("NonNull", size), &list, |b, list| {
+            b.iter(|| list.iter().map(|&val| u64::from(val)).sum::<u64>())
+        });
+
+        let mut rc_list = RcList::new();
+        (0..size).for_each(|i| rc_list.push_back(i));
+        group.bench_with_input(BenchmarkId::new("RcRefCell", size), &rc_list, |b, list| {
+            b.iter(|| list.sum())
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, push_pop, traverse);
+criterion_main!(benches);
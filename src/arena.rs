@@ -0,0 +1,920 @@
+use std::fmt;
+use std::iter::FusedIterator;
+use std::marker::PhantomData;
+use std::mem;
+
+use crate::dll::StaleHandleError;
+
+// Index of a slot in the arena
+type Index = u32;
+// Type alias to make the code more readable
+type Link = Option<Index>;
+
+/// A slot of the arena, either holding a linked element or sitting on the
+/// free list.
+#[derive(Clone)]
+enum Entry<T> {
+    Occupied { val: T, next: Link, prev: Link },
+    Vacant { next_free: Link },
+}
+
+/// An arena slot. The generation is bumped every time the slot is vacated,
+/// so handles to the previous occupant no longer match.
+#[derive(Clone)]
+struct Slot<T> {
+    generation: u32,
+    entry: Entry<T>,
+}
+
+/// A doubly linked list whose nodes live in one contiguous `Vec` instead of
+/// individual heap allocations.
+///
+/// `next`/`prev` links are `u32` slot indices and removed slots are recycled
+/// through a free list, so a list that is pushed and popped in a loop stops
+/// allocating once it has reached its peak length. Elements can be addressed
+/// by generational [`ArenaHandle`]s, which detect reuse of their slot.
+///
+/// ```
+/// use dll_rs::ArenaList;
+///
+/// let mut list = ArenaList::with_capacity(16);
+/// list.push_back(2);
+/// let one = list.push_front_with_handle(1);
+/// assert_eq!(list.pop_back(), Some(2));
+/// assert_eq!(list.remove_by_handle(one), Ok(1));
+/// assert!(list.get_by_handle(one).is_err());
+/// ```
+#[derive(Clone)]
+pub struct ArenaList<T> {
+    slots: Vec<Slot<T>>,
+    head: Link,
+    tail: Link,
+    free: Link, // Head of the free list, threaded through `Entry::Vacant`
+    len: usize,
+    // Generation new slots start at, so handles into slots dropped by
+    // `shrink_to_fit` cannot match a slot pushed at the same index later
+    base_generation: u32,
+}
+
+/// A generational handle to an element of an [`ArenaList`], returned by
+/// [`ArenaList::push_front_with_handle`] and [`ArenaList::push_back_with_handle`].
+///
+/// Handles are plain indices: they are `Copy`, and using one with a list
+/// other than the one that issued it is not detected.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ArenaHandle {
+    index: Index,
+    generation: u32,
+}
+
+impl<T> ArenaList<T> {
+    /// Creates a new, empty list without allocating.
+    pub fn new() -> Self {
+        ArenaList {
+            slots: Vec::new(),
+            head: None,
+            tail: None,
+            free: None,
+            len: 0,
+            base_generation: 0,
+        }
+    }
+
+    /// Creates a new, empty list with room for `capacity` elements.
+    pub fn with_capacity(capacity: usize) -> Self {
+        ArenaList {
+            slots: Vec::with_capacity(capacity),
+            ..ArenaList::new()
+        }
+    }
+
+    /// Returns the number of elements in the list.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns true if the list contains no elements.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the number of elements the list can hold without reallocating.
+    pub fn capacity(&self) -> usize {
+        // Vacant slots are reused before the `Vec` grows
+        self.slots.capacity()
+    }
+
+    /// Reserves room for at least `additional` more elements.
+    pub fn reserve(&mut self, additional: usize) {
+        let vacant = self.slots.len() - self.len;
+        self.slots.reserve(additional.saturating_sub(vacant));
+    }
+
+    /// Shrinks the arena as much as possible without moving any element.
+    ///
+    /// Vacant slots after the last occupied one are released; vacant slots
+    /// in between have to stay, as handles and links refer to elements by
+    /// their slot index.
+    pub fn shrink_to_fit(&mut self) {
+        let keep = self
+            .slots
+            .iter()
+            .rposition(|slot| matches!(slot.entry, Entry::Occupied { .. }))
+            .map_or(0, |last| last + 1);
+        for slot in self.slots.drain(keep..) {
+            self.base_generation = self.base_generation.max(slot.generation);
+        }
+        self.slots.shrink_to_fit();
+
+        // Rebuild the free list out of the remaining vacant slots
+        self.free = None;
+        for (index, slot) in self.slots.iter_mut().enumerate().rev() {
+            if let Entry::Vacant { next_free } = &mut slot.entry {
+                *next_free = self.free;
+                self.free = Some(index as Index);
+            }
+        }
+    }
+
+    /// Removes all elements from the list, keeping the arena's allocation.
+    pub fn clear(&mut self) {
+        while self.pop_front().is_some() {}
+    }
+
+    /// Returns a reference to the first element, or `None` if the list is empty.
+    pub fn front(&self) -> Option<&T> {
+        self.head.map(|index| self.val(index))
+    }
+
+    /// Returns a reference to the last element, or `None` if the list is empty.
+    pub fn back(&self) -> Option<&T> {
+        self.tail.map(|index| self.val(index))
+    }
+
+    /// Returns a mutable reference to the first element, or `None` if the
+    /// list is empty.
+    pub fn front_mut(&mut self) -> Option<&mut T> {
+        self.head.map(|index| self.val_mut(index))
+    }
+
+    /// Returns a mutable reference to the last element, or `None` if the
+    /// list is empty.
+    pub fn back_mut(&mut self) -> Option<&mut T> {
+        self.tail.map(|index| self.val_mut(index))
+    }
+
+    /// Adds an element to the front of the list.
+    pub fn push_front(&mut self, val: T) {
+        self.push_front_with_handle(val);
+    }
+
+    /// Adds an element to the back of the list.
+    pub fn push_back(&mut self, val: T) {
+        self.push_back_with_handle(val);
+    }
+
+    /// Adds an element to the front of the list and returns a handle to it.
+    pub fn push_front_with_handle(&mut self, val: T) -> ArenaHandle {
+        let index = self.alloc(val);
+        self.link_between(None, self.head, index);
+        self.handle(index)
+    }
+
+    /// Adds an element to the back of the list and returns a handle to it.
+    pub fn push_back_with_handle(&mut self, val: T) -> ArenaHandle {
+        let index = self.alloc(val);
+        self.link_between(self.tail, None, index);
+        self.handle(index)
+    }
+
+    /// Removes the first element and returns it, or `None` if the list is empty.
+    pub fn pop_front(&mut self) -> Option<T> {
+        self.head.map(|index| self.remove_at(index))
+    }
+
+    /// Removes the last element and returns it, or `None` if the list is empty.
+    pub fn pop_back(&mut self) -> Option<T> {
+        self.tail.map(|index| self.remove_at(index))
+    }
+
+    /// Returns a reference to the element behind `handle`.
+    pub fn get_by_handle(&self, handle: ArenaHandle) -> Result<&T, StaleHandleError> {
+        self.resolve(handle).map(|index| self.val(index))
+    }
+
+    /// Returns a mutable reference to the element behind `handle`.
+    pub fn get_mut_by_handle(&mut self, handle: ArenaHandle) -> Result<&mut T, StaleHandleError> {
+        self.resolve(handle).map(|index| self.val_mut(index))
+    }
+
+    /// Removes the element behind `handle` in O(1) and returns it.
+    pub fn remove_by_handle(&mut self, handle: ArenaHandle) -> Result<T, StaleHandleError> {
+        self.resolve(handle).map(|index| self.remove_at(index))
+    }
+
+    /// Moves the element behind `handle` to the front of the list in O(1).
+    pub fn move_to_front(&mut self, handle: ArenaHandle) -> Result<(), StaleHandleError> {
+        let index = self.resolve(handle)?;
+        self.unlink(index);
+        self.link_between(None, self.head, index);
+        Ok(())
+    }
+
+    /// Moves the element behind `handle` to the back of the list in O(1).
+    pub fn move_to_back(&mut self, handle: ArenaHandle) -> Result<(), StaleHandleError> {
+        let index = self.resolve(handle)?;
+        self.unlink(index);
+        self.link_between(self.tail, None, index);
+        Ok(())
+    }
+
+    /// Returns an iterator over references to the elements, front to back.
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            list: self,
+            head: self.head,
+            tail: self.tail,
+            len: self.len,
+        }
+    }
+
+    /// Returns an iterator over mutable references to the elements, front to back.
+    pub fn iter_mut(&mut self) -> IterMut<'_, T> {
+        IterMut {
+            slots: self.slots.as_mut_ptr(),
+            head: self.head,
+            tail: self.tail,
+            len: self.len,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Returns a cursor pointing at the first element, or at the "ghost"
+    /// non-element if the list is empty.
+    pub fn cursor_front(&self) -> Cursor<'_, T> {
+        Cursor {
+            list: self,
+            current: self.head,
+            index: 0,
+        }
+    }
+
+    /// Returns a cursor pointing at the last element, or at the "ghost"
+    /// non-element if the list is empty.
+    pub fn cursor_back(&self) -> Cursor<'_, T> {
+        Cursor {
+            list: self,
+            current: self.tail,
+            index: self.len.saturating_sub(1),
+        }
+    }
+
+    /// Returns a mutable cursor pointing at the first element, or at the
+    /// "ghost" non-element if the list is empty.
+    pub fn cursor_front_mut(&mut self) -> CursorMut<'_, T> {
+        CursorMut {
+            current: self.head,
+            index: 0,
+            list: self,
+        }
+    }
+
+    /// Returns a mutable cursor pointing at the last element, or at the
+    /// "ghost" non-element if the list is empty.
+    pub fn cursor_back_mut(&mut self) -> CursorMut<'_, T> {
+        CursorMut {
+            current: self.tail,
+            index: self.len.saturating_sub(1),
+            list: self,
+        }
+    }
+
+    fn handle(&self, index: Index) -> ArenaHandle {
+        ArenaHandle {
+            index,
+            generation: self.slots[index as usize].generation,
+        }
+    }
+
+    fn resolve(&self, handle: ArenaHandle) -> Result<Index, StaleHandleError> {
+        match self.slots.get(handle.index as usize) {
+            Some(Slot {
+                generation,
+                entry: Entry::Occupied { .. },
+            }) if *generation == handle.generation => Ok(handle.index),
+            _ => Err(StaleHandleError),
+        }
+    }
+
+    /// Returns the `(next, prev)` links of an occupied slot.
+    fn links(&self, index: Index) -> (Link, Link) {
+        match &self.slots[index as usize].entry {
+            Entry::Occupied { next, prev, .. } => (*next, *prev),
+            Entry::Vacant { .. } => unreachable!("linked slot {index} is vacant"),
+        }
+    }
+
+    fn links_mut(&mut self, index: Index) -> (&mut Link, &mut Link) {
+        match &mut self.slots[index as usize].entry {
+            Entry::Occupied { next, prev, .. } => (next, prev),
+            Entry::Vacant { .. } => unreachable!("linked slot {index} is vacant"),
+        }
+    }
+
+    fn val(&self, index: Index) -> &T {
+        match &self.slots[index as usize].entry {
+            Entry::Occupied { val, .. } => val,
+            Entry::Vacant { .. } => unreachable!("linked slot {index} is vacant"),
+        }
+    }
+
+    fn val_mut(&mut self, index: Index) -> &mut T {
+        match &mut self.slots[index as usize].entry {
+            Entry::Occupied { val, .. } => val,
+            Entry::Vacant { .. } => unreachable!("linked slot {index} is vacant"),
+        }
+    }
+
+    /// Stores `val` in a free slot, reusing vacant ones first, and returns
+    /// the slot's index. The slot is not linked yet.
+    fn alloc(&mut self, val: T) -> Index {
+        let entry = Entry::Occupied {
+            val,
+            next: None,
+            prev: None,
+        };
+        match self.free {
+            Some(index) => {
+                let slot = &mut self.slots[index as usize];
+                match mem::replace(&mut slot.entry, entry) {
+                    Entry::Vacant { next_free } => self.free = next_free,
+                    Entry::Occupied { .. } => unreachable!("free slot {index} is occupied"),
+                }
+                index
+            }
+            None => {
+                let index = Index::try_from(self.slots.len())
+                    .ok()
+                    .filter(|&index| index != Index::MAX)
+                    .expect("ArenaList cannot hold more than u32::MAX - 1 elements");
+                self.slots.push(Slot {
+                    generation: self.base_generation,
+                    entry,
+                });
+                index
+            }
+        }
+    }
+
+    /// Links the unlinked slot `index` between the adjacent `prev` and `next`.
+    fn link_between(&mut self, prev: Link, next: Link, index: Index) {
+        *self.links_mut(index).0 = next;
+        *self.links_mut(index).1 = prev;
+        match next {
+            Some(next) => *self.links_mut(next).1 = Some(index),
+            None => self.tail = Some(index),
+        }
+        match prev {
+            Some(prev) => *self.links_mut(prev).0 = Some(index),
+            None => self.head = Some(index),
+        }
+        self.len += 1;
+    }
+
+    /// Unlinks slot `index` and joins its neighbours; the slot stays occupied.
+    fn unlink(&mut self, index: Index) {
+        let (next, prev) = self.links(index);
+        match next {
+            Some(next) => *self.links_mut(next).1 = prev,
+            None => self.tail = prev,
+        }
+        match prev {
+            Some(prev) => *self.links_mut(prev).0 = next,
+            None => self.head = next,
+        }
+        self.len -= 1;
+    }
+
+    /// Unlinks slot `index`, puts it on the free list and returns its value.
+    fn remove_at(&mut self, index: Index) -> T {
+        self.unlink(index);
+        let slot = &mut self.slots[index as usize];
+        slot.generation = slot.generation.wrapping_add(1);
+        let vacant = Entry::Vacant {
+            next_free: self.free,
+        };
+        self.free = Some(index);
+        match mem::replace(&mut slot.entry, vacant) {
+            Entry::Occupied { val, .. } => val,
+            Entry::Vacant { .. } => unreachable!("linked slot {index} is vacant"),
+        }
+    }
+}
+
+impl<T> Default for ArenaList<T> {
+    /// Creates an empty `ArenaList<T>`.
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for ArenaList<T> {
+    /// Formats the list as its elements, front to back, e.g. `[1, 2, 3]`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self).finish()
+    }
+}
+
+impl<T: PartialEq> PartialEq for ArenaList<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len == other.len && self.iter().eq(other)
+    }
+}
+
+impl<T: Eq> Eq for ArenaList<T> {}
+
+impl<T> FromIterator<T> for ArenaList<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut list = ArenaList::new();
+        list.extend(iter);
+        list
+    }
+}
+
+impl<T> Extend<T> for ArenaList<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        let iter = iter.into_iter();
+        self.reserve(iter.size_hint().0);
+        iter.for_each(|val| self.push_back(val));
+    }
+}
+
+/// Immutable iterator over an `ArenaList`, created by [`ArenaList::iter`].
+pub struct Iter<'a, T> {
+    list: &'a ArenaList<T>,
+    head: Link,
+    tail: Link,
+    len: usize,
+}
+
+impl<T> Clone for Iter<'_, T> {
+    fn clone(&self) -> Self {
+        Iter { ..*self }
+    }
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.len == 0 {
+            return None;
+        }
+        self.head.map(|index| {
+            self.len -= 1;
+            self.head = self.list.links(index).0;
+            self.list.val(index)
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<T> DoubleEndedIterator for Iter<'_, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.len == 0 {
+            return None;
+        }
+        self.tail.map(|index| {
+            self.len -= 1;
+            self.tail = self.list.links(index).1;
+            self.list.val(index)
+        })
+    }
+}
+
+impl<T> ExactSizeIterator for Iter<'_, T> {}
+
+impl<T> FusedIterator for Iter<'_, T> {}
+
+/// Mutable iterator over an `ArenaList`, created by [`ArenaList::iter_mut`].
+///
+/// The links visit every occupied slot at most once, and the remaining `len`
+/// keeps the two ends from meeting, so each yielded `&mut T` is unique.
+pub struct IterMut<'a, T> {
+    slots: *mut Slot<T>,
+    head: Link,
+    tail: Link,
+    len: usize,
+    _marker: PhantomData<&'a mut [Slot<T>]>,
+}
+
+impl<'a, T> IterMut<'a, T> {
+    /// Returns the value and `(next, prev)` links of a linked slot.
+    fn take(&mut self, index: Index) -> (&'a mut T, Link, Link) {
+        // SAFETY: linked indices are in bounds of the exclusively borrowed
+        // slots, and every slot is taken at most once (see the type docs)
+        let slot = unsafe { &mut *self.slots.add(index as usize) };
+        match &mut slot.entry {
+            Entry::Occupied { val, next, prev } => (val, *next, *prev),
+            Entry::Vacant { .. } => unreachable!("linked slot {index} is vacant"),
+        }
+    }
+}
+
+impl<'a, T> Iterator for IterMut<'a, T> {
+    type Item = &'a mut T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.len == 0 {
+            return None;
+        }
+        self.head.map(|index| {
+            self.len -= 1;
+            let (val, next, _) = self.take(index);
+            self.head = next;
+            val
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<T> DoubleEndedIterator for IterMut<'_, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.len == 0 {
+            return None;
+        }
+        self.tail.map(|index| {
+            self.len -= 1;
+            let (val, _, prev) = self.take(index);
+            self.tail = prev;
+            val
+        })
+    }
+}
+
+impl<T> ExactSizeIterator for IterMut<'_, T> {}
+
+impl<T> FusedIterator for IterMut<'_, T> {}
+
+/// Owning iterator over an `ArenaList`, created by its `IntoIterator` impl.
+pub struct IntoIter<T> {
+    list: ArenaList<T>,
+}
+
+impl<T> Iterator for IntoIter<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.list.pop_front()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.list.len, Some(self.list.len))
+    }
+}
+
+impl<T> DoubleEndedIterator for IntoIter<T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.list.pop_back()
+    }
+}
+
+impl<T> ExactSizeIterator for IntoIter<T> {}
+
+impl<T> FusedIterator for IntoIter<T> {}
+
+impl<T> IntoIterator for ArenaList<T> {
+    type Item = T;
+    type IntoIter = IntoIter<T>;
+
+    /// Consumes the list into an iterator yielding elements by value.
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter { list: self }
+    }
+}
+
+impl<'a, T> IntoIterator for &'a ArenaList<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, T> IntoIterator for &'a mut ArenaList<T> {
+    type Item = &'a mut T;
+    type IntoIter = IterMut<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+/// A cursor over an `ArenaList`, see [`crate::Cursor`] for the semantics.
+pub struct Cursor<'a, T> {
+    list: &'a ArenaList<T>,
+    current: Link,
+    index: usize,
+}
+
+impl<T> Clone for Cursor<'_, T> {
+    fn clone(&self) -> Self {
+        Cursor { ..*self }
+    }
+}
+
+impl<'a, T> Cursor<'a, T> {
+    /// Returns the index of the current element, or `None` at the ghost.
+    pub fn index(&self) -> Option<usize> {
+        self.current.map(|_| self.index)
+    }
+
+    /// Moves to the next element; from the tail this moves to the ghost and
+    /// from the ghost to the head.
+    pub fn move_next(&mut self) {
+        match self.current {
+            None => {
+                self.current = self.list.head;
+                self.index = 0;
+            }
+            Some(current) => {
+                self.current = self.list.links(current).0;
+                self.index += 1;
+            }
+        }
+    }
+
+    /// Moves to the previous element; from the head this moves to the ghost
+    /// and from the ghost to the tail.
+    pub fn move_prev(&mut self) {
+        match self.current {
+            None => {
+                self.current = self.list.tail;
+                self.index = self.list.len.saturating_sub(1);
+            }
+            Some(current) => {
+                self.current = self.list.links(current).1;
+                match self.current {
+                    Some(_) => self.index -= 1,
+                    None => self.index = self.list.len,
+                }
+            }
+        }
+    }
+
+    /// Returns the current element, or `None` at the ghost.
+    pub fn current(&self) -> Option<&'a T> {
+        self.current.map(|index| self.list.val(index))
+    }
+
+    /// Returns the next element without moving; at the ghost this is the head.
+    pub fn peek_next(&self) -> Option<&'a T> {
+        let next = match self.current {
+            None => self.list.head,
+            Some(current) => self.list.links(current).0,
+        };
+        next.map(|index| self.list.val(index))
+    }
+
+    /// Returns the previous element without moving; at the ghost this is the tail.
+    pub fn peek_prev(&self) -> Option<&'a T> {
+        let prev = match self.current {
+            None => self.list.tail,
+            Some(current) => self.list.links(current).1,
+        };
+        prev.map(|index| self.list.val(index))
+    }
+}
+
+/// A cursor over an `ArenaList` with editing operations, see
+/// [`crate::CursorMut`] for the semantics.
+///
+/// Splitting and splicing are not offered: moving elements between arenas
+/// means moving every value, so they would not be O(1).
+pub struct CursorMut<'a, T> {
+    list: &'a mut ArenaList<T>,
+    current: Link,
+    index: usize,
+}
+
+impl<T> CursorMut<'_, T> {
+    fn next_link(&self) -> Link {
+        match self.current {
+            None => self.list.head,
+            Some(current) => self.list.links(current).0,
+        }
+    }
+
+    fn prev_link(&self) -> Link {
+        match self.current {
+            None => self.list.tail,
+            Some(current) => self.list.links(current).1,
+        }
+    }
+
+    /// Returns the index of the current element, or `None` at the ghost.
+    pub fn index(&self) -> Option<usize> {
+        self.current.map(|_| self.index)
+    }
+
+    /// Moves to the next element; from the tail this moves to the ghost and
+    /// from the ghost to the head.
+    pub fn move_next(&mut self) {
+        match self.current {
+            None => self.index = 0,
+            Some(_) => self.index += 1,
+        }
+        self.current = self.next_link();
+    }
+
+    /// Moves to the previous element; from the head this moves to the ghost
+    /// and from the ghost to the tail.
+    pub fn move_prev(&mut self) {
+        // The ghost sits at index `len`, so the tail is always one step back
+        self.current = self.prev_link();
+        match self.current {
+            Some(_) => self.index -= 1,
+            None => self.index = self.list.len,
+        }
+    }
+
+    /// Returns the current element, or `None` at the ghost.
+    pub fn current(&mut self) -> Option<&mut T> {
+        self.current.map(|index| self.list.val_mut(index))
+    }
+
+    /// Returns the next element without moving; at the ghost this is the head.
+    pub fn peek_next(&mut self) -> Option<&mut T> {
+        self.next_link().map(|index| self.list.val_mut(index))
+    }
+
+    /// Returns the previous element without moving; at the ghost this is the tail.
+    pub fn peek_prev(&mut self) -> Option<&mut T> {
+        self.prev_link().map(|index| self.list.val_mut(index))
+    }
+
+    /// Returns a read-only cursor pointing at the same position.
+    pub fn as_cursor(&self) -> Cursor<'_, T> {
+        Cursor {
+            list: self.list,
+            current: self.current,
+            index: self.index,
+        }
+    }
+
+    /// Returns a handle to the current element, or `None` at the ghost.
+    pub fn current_handle(&self) -> Option<ArenaHandle> {
+        self.current.map(|index| self.list.handle(index))
+    }
+
+    /// Inserts `val` after the current element; at the ghost it becomes the
+    /// new head.
+    pub fn insert_after(&mut self, val: T) -> ArenaHandle {
+        let index = self.list.alloc(val);
+        let next = self.next_link();
+        self.list.link_between(self.current, next, index);
+        if self.current.is_none() {
+            self.index += 1;
+        }
+        self.list.handle(index)
+    }
+
+    /// Inserts `val` before the current element; at the ghost it becomes the
+    /// new tail.
+    pub fn insert_before(&mut self, val: T) -> ArenaHandle {
+        let index = self.list.alloc(val);
+        let prev = self.prev_link();
+        self.list.link_between(prev, self.current, index);
+        self.index += 1;
+        self.list.handle(index)
+    }
+
+    /// Removes the current element and returns it, moving the cursor to the
+    /// next element. Returns `None` at the ghost.
+    pub fn remove_current(&mut self) -> Option<T> {
+        let current = self.current?;
+        self.current = self.next_link();
+        Some(self.list.remove_at(current))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dll::collect;
+
+    #[test]
+    fn test_push_pop_reuses_slots() {
+        let mut list = ArenaList::new();
+        list.push_back(2);
+        list.push_front(1);
+        list.push_back(3);
+        assert_eq!(collect(&list), [1, 2, 3]);
+        assert_eq!((list.front(), list.back()), (Some(&1), Some(&3)));
+
+        assert_eq!(list.pop_front(), Some(1));
+        assert_eq!(list.pop_back(), Some(3));
+        let slots = list.slots.len();
+        list.push_back(4);
+        list.push_front(0);
+        assert_eq!(list.slots.len(), slots, "vacant slots are reused");
+        assert_eq!(collect(&list), [0, 2, 4]);
+        list.clear();
+        assert_eq!(list.pop_back(), None);
+    }
+
+    #[test]
+    fn test_handles_are_generational() {
+        let mut list = ArenaList::new();
+        let a = list.push_back_with_handle(1);
+        let b = list.push_back_with_handle(2);
+        assert_eq!(list.remove_by_handle(a), Ok(1));
+
+        // The next push reuses `a`'s slot with a new generation
+        let c = list.push_front_with_handle(3);
+        assert_eq!(c.index, a.index);
+        assert_eq!(list.get_by_handle(a), Err(StaleHandleError));
+        assert_eq!(list.move_to_back(a), Err(StaleHandleError));
+        *list.get_mut_by_handle(c).unwrap() += 10;
+        list.move_to_back(c).unwrap();
+        list.move_to_front(c).unwrap();
+        list.move_to_back(b).unwrap();
+        assert_eq!(collect(&list), [13, 2]);
+    }
+
+    #[test]
+    fn test_capacity() {
+        let mut list: ArenaList<i32> = ArenaList::with_capacity(4);
+        assert!(list.capacity() >= 4);
+        list.extend(0..8);
+        list.reserve(10);
+        assert!(list.capacity() >= 18);
+
+        let handles: Vec<_> = (8..12).map(|i| list.push_back_with_handle(i)).collect();
+        for handle in &handles {
+            list.remove_by_handle(*handle).unwrap();
+        }
+        list.pop_front();
+        list.shrink_to_fit();
+        assert_eq!(list.slots.len(), 8);
+        assert_eq!(collect(&list), [1, 2, 3, 4, 5, 6, 7]);
+
+        // Slots released by `shrink_to_fit` come back with a fresh generation
+        let handle = list.push_back_with_handle(8);
+        assert_eq!(list.get_by_handle(handles[0]), Err(StaleHandleError));
+        assert_eq!(list.get_by_handle(handle), Ok(&8));
+        list.push_back(9);
+        assert_eq!(collect(&list), [1, 2, 3, 4, 5, 6, 7, 8, 9]);
+    }
+
+    #[test]
+    fn test_iter_mut_and_into_iter() {
+        let mut list: ArenaList<_> = (1..=4).collect();
+        let mut iter = list.iter_mut();
+        *iter.next().unwrap() *= 10;
+        *iter.next_back().unwrap() *= 40;
+        assert_eq!(iter.len(), 2);
+        for val in &mut list {
+            *val += 1;
+        }
+        assert_eq!(
+            list.clone().into_iter().rev().collect::<Vec<_>>(),
+            [161, 4, 3, 11]
+        );
+        assert_eq!(format!("{list:?}"), "[11, 3, 4, 161]");
+    }
+
+    #[test]
+    fn test_cursor() {
+        let mut list: ArenaList<_> = (1..=3).collect();
+        let mut cursor = list.cursor_front_mut();
+        cursor.move_next();
+        cursor.insert_before(10);
+        let handle = cursor.insert_after(20);
+        assert_eq!(cursor.index(), Some(2));
+        assert_eq!(cursor.remove_current(), Some(2));
+        assert_eq!(cursor.current_handle(), Some(handle));
+        cursor.move_next();
+        cursor.move_next();
+        assert_eq!(cursor.index(), None);
+        cursor.insert_after(0);
+        cursor.move_prev();
+        assert_eq!((cursor.index(), cursor.current()), (Some(4), Some(&mut 3)));
+        assert_eq!(cursor.as_cursor().peek_prev(), Some(&20));
+        assert_eq!(collect(&list), [0, 1, 10, 20, 3]);
+
+        let mut cursor = list.cursor_back();
+        cursor.move_next();
+        assert_eq!(cursor.peek_next(), Some(&0));
+        cursor.move_prev();
+        cursor.move_prev();
+        assert_eq!((cursor.index(), cursor.current()), (Some(3), Some(&20)));
+    }
+}
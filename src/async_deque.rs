@@ -0,0 +1,663 @@
+use std::fmt;
+use std::future::Future;
+use std::iter;
+use std::pin::Pin;
+use std::sync::{Mutex, MutexGuard};
+use std::task::{Context, Poll, Waker};
+
+use futures_core::{FusedStream, Stream};
+
+use crate::blocking::{End, SendList};
+use crate::{ArenaHandle, ArenaList, DoublyLinkedList, PopError, PushError};
+
+struct State<T> {
+    list: SendList<T>,
+    closed: bool,
+    // Tasks waiting for an element and for room, in the order they arrived
+    poppers: ArenaList<Waker>,
+    pushers: ArenaList<Waker>,
+}
+
+/// Which queue of waiting tasks a waiter sits on.
+#[derive(Clone, Copy)]
+enum Queue {
+    Poppers,
+    Pushers,
+}
+
+impl<T> State<T> {
+    fn queue(&mut self, queue: Queue) -> &mut ArenaList<Waker> {
+        match queue {
+            Queue::Poppers => &mut self.poppers,
+            Queue::Pushers => &mut self.pushers,
+        }
+    }
+}
+
+/// A task's place in one of the waiter queues.
+///
+/// Waking a task removes its waker from the queue, which leaves the handle
+/// stale. A waiter dropped with a stale handle was woken for nothing, so it
+/// passes the wakeup on to the next task in line.
+struct Waiter {
+    queue: Queue,
+    handle: Option<ArenaHandle>,
+}
+
+impl Waiter {
+    fn new(queue: Queue) -> Self {
+        Waiter {
+            queue,
+            handle: None,
+        }
+    }
+
+    /// Queues `waker`, or refreshes it if this waiter is still queued.
+    fn register<T>(&mut self, state: &mut State<T>, waker: &Waker) {
+        let queue = state.queue(self.queue);
+        if let Some(queued) = self.handle.and_then(|h| queue.get_mut_by_handle(h).ok()) {
+            queued.clone_from(waker);
+        } else {
+            self.handle = Some(queue.push_back_with_handle(waker.clone()));
+        }
+    }
+
+    /// Leaves the queue once the operation completed. A wakeup this waiter
+    /// received was used up by the operation itself.
+    fn finish<T>(&mut self, state: &mut State<T>) {
+        if let Some(handle) = self.handle.take() {
+            let _ = state.queue(self.queue).remove_by_handle(handle);
+        }
+    }
+
+    /// Leaves the queue before the operation completed. Returns the waker
+    /// of the next task in line if this waiter had already been woken.
+    fn cancel<T>(&mut self, state: &mut State<T>) -> Option<Waker> {
+        let handle = self.handle.take()?;
+        let queue = state.queue(self.queue);
+        match queue.remove_by_handle(handle) {
+            Ok(_) => None,
+            Err(_) => queue.pop_front(),
+        }
+    }
+}
+
+/// A `DoublyLinkedList` behind a mutex, for handing work between async
+/// tasks. Needs the `async` feature.
+///
+/// This is the async counterpart of [`BlockingDeque`](crate::BlockingDeque):
+/// [`AsyncDeque::pop_front`] suspends the task while the deque is empty,
+/// and pushing onto a deque created with [`AsyncDeque::bounded`] suspends
+/// it while the deque is full. Tasks are woken through their `Waker`, so
+/// the deque works on any executor. Waiting tasks are served in the order
+/// they started waiting.
+///
+/// [`AsyncDeque::close`] wakes up all waiting tasks: pushes fail from then
+/// on, while pops keep returning the remaining elements until the deque is
+/// drained.
+///
+/// ```
+/// use futures::executor::block_on;
+///
+/// use dll_rs::AsyncDeque;
+///
+/// let deque = AsyncDeque::bounded(2);
+/// block_on(async {
+///     deque.push_back(2).await.unwrap();
+///     deque.push_front(1).await.unwrap();
+///     deque.close();
+///     assert_eq!(deque.pop_front().await, Some(1));
+///     assert_eq!(deque.pop_back().await, Some(2));
+///     assert_eq!(deque.pop_back().await, None);
+/// });
+/// ```
+pub struct AsyncDeque<T> {
+    state: Mutex<State<T>>,
+    capacity: Option<usize>,
+}
+
+impl<T> AsyncDeque<T> {
+    /// Creates a new, empty deque without a capacity bound. Pushes never
+    /// suspend.
+    pub fn new() -> Self {
+        Self::with_bound(None)
+    }
+
+    /// Creates a new, empty deque holding at most `capacity` elements.
+    ///
+    /// # Panics
+    /// Panics if `capacity` is 0, as nothing could ever be pushed.
+    pub fn bounded(capacity: usize) -> Self {
+        assert!(capacity > 0, "capacity of an AsyncDeque must be positive");
+        Self::with_bound(Some(capacity))
+    }
+
+    fn with_bound(capacity: Option<usize>) -> Self {
+        AsyncDeque {
+            state: Mutex::new(State {
+                list: SendList(DoublyLinkedList::new()),
+                closed: false,
+                poppers: ArenaList::new(),
+                pushers: ArenaList::new(),
+            }),
+            capacity,
+        }
+    }
+
+    /// Returns the capacity bound, or `None` if the deque is unbounded.
+    pub fn capacity(&self) -> Option<usize> {
+        self.capacity
+    }
+
+    /// Returns the number of elements in the deque.
+    pub fn len(&self) -> usize {
+        self.lock().list.0.len()
+    }
+
+    /// Returns true if the deque contains no elements.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns true if [`AsyncDeque::close`] has been called.
+    pub fn is_closed(&self) -> bool {
+        self.lock().closed
+    }
+
+    /// Closes the deque and wakes up every waiting task. Elements that are
+    /// still queued can be popped as usual.
+    pub fn close(&self) {
+        let woken: Vec<_> = {
+            let mut state = self.lock();
+            state.closed = true;
+            // Drained rather than replaced, so the handles of the woken
+            // waiters stay stale
+            iter::from_fn(|| {
+                state
+                    .poppers
+                    .pop_front()
+                    .or_else(|| state.pushers.pop_front())
+            })
+            .collect()
+        };
+        woken.into_iter().for_each(Waker::wake);
+    }
+
+    /// Adds an element to the front, waiting while the deque is full.
+    pub async fn push_front(&self, val: T) -> Result<(), PushError<T>> {
+        self.push(End::Front, val).await
+    }
+
+    /// Adds an element to the back, waiting while the deque is full.
+    pub async fn push_back(&self, val: T) -> Result<(), PushError<T>> {
+        self.push(End::Back, val).await
+    }
+
+    /// Adds an element to the front, or fails right away if the deque is full.
+    pub fn try_push_front(&self, val: T) -> Result<(), PushError<T>> {
+        self.try_push(End::Front, val)
+    }
+
+    /// Adds an element to the back, or fails right away if the deque is full.
+    pub fn try_push_back(&self, val: T) -> Result<(), PushError<T>> {
+        self.try_push(End::Back, val)
+    }
+
+    /// Removes the first element, waiting while the deque is empty.
+    /// Returns `None` once the deque is closed and drained.
+    pub async fn pop_front(&self) -> Option<T> {
+        self.pop(End::Front).await
+    }
+
+    /// Removes the last element, waiting while the deque is empty.
+    /// Returns `None` once the deque is closed and drained.
+    pub async fn pop_back(&self) -> Option<T> {
+        self.pop(End::Back).await
+    }
+
+    /// Removes the first element, or fails right away if the deque is empty.
+    pub fn try_pop_front(&self) -> Result<T, PopError> {
+        self.try_pop(End::Front)
+    }
+
+    /// Removes the last element, or fails right away if the deque is empty.
+    pub fn try_pop_back(&self) -> Result<T, PopError> {
+        self.try_pop(End::Back)
+    }
+
+    /// Returns a stream popping elements from the front, which ends once
+    /// the deque is closed and drained.
+    ///
+    /// ```
+    /// use futures::executor::block_on;
+    /// use futures::StreamExt;
+    ///
+    /// use dll_rs::AsyncDeque;
+    ///
+    /// let deque = AsyncDeque::new();
+    /// (1..=3).for_each(|i| deque.try_push_back(i).unwrap());
+    /// deque.close();
+    /// let popped: Vec<_> = block_on(deque.stream().collect());
+    /// assert_eq!(popped, [1, 2, 3]);
+    /// ```
+    pub fn stream(&self) -> PopStream<'_, T> {
+        PopStream {
+            deque: self,
+            waiter: Waiter::new(Queue::Poppers),
+            done: false,
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, State<T>> {
+        // Every critical section leaves the state consistent before
+        // anything that could panic
+        self.state
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn is_full(&self, state: &State<T>) -> bool {
+        self.capacity
+            .is_some_and(|capacity| state.list.0.len() >= capacity)
+    }
+
+    fn push(&self, end: End, val: T) -> Push<'_, T> {
+        Push {
+            deque: self,
+            end,
+            val: Some(val),
+            waiter: Waiter::new(Queue::Pushers),
+        }
+    }
+
+    fn pop(&self, end: End) -> Pop<'_, T> {
+        Pop {
+            deque: self,
+            end,
+            waiter: Waiter::new(Queue::Poppers),
+        }
+    }
+
+    fn try_push(&self, end: End, val: T) -> Result<(), PushError<T>> {
+        let mut state = self.lock();
+        let full = self.is_full(&state);
+        let woken = Self::push_locked(full, &mut state, end, val)?;
+        drop(state);
+        if let Some(waker) = woken {
+            waker.wake();
+        }
+        Ok(())
+    }
+
+    fn try_pop(&self, end: End) -> Result<T, PopError> {
+        let mut state = self.lock();
+        let (val, woken) = self.pop_locked(&mut state, end)?;
+        drop(state);
+        if let Some(waker) = woken {
+            waker.wake();
+        }
+        Ok(val)
+    }
+
+    /// Pushes `val` unless the deque is closed or `full`. Returns the waker
+    /// of the task now able to pop.
+    fn push_locked(
+        full: bool,
+        state: &mut State<T>,
+        end: End,
+        val: T,
+    ) -> Result<Option<Waker>, PushError<T>> {
+        if state.closed {
+            return Err(PushError::Closed(val));
+        }
+        if full {
+            return Err(PushError::Full(val));
+        }
+        match end {
+            End::Front => state.list.0.push_front(val),
+            End::Back => state.list.0.push_back(val),
+        }
+        Ok(state.poppers.pop_front())
+    }
+
+    /// Pops an element if there is one. Returns it along with the waker of
+    /// the task now able to push.
+    fn pop_locked(&self, state: &mut State<T>, end: End) -> Result<(T, Option<Waker>), PopError> {
+        let val = match end {
+            End::Front => state.list.0.pop_front(),
+            End::Back => state.list.0.pop_back(),
+        };
+        match val {
+            Some(val) => {
+                let woken = self.capacity.and_then(|_| state.pushers.pop_front());
+                Ok((val, woken))
+            }
+            None if state.closed => Err(PopError::Closed),
+            None => Err(PopError::Empty),
+        }
+    }
+
+    /// Pops an element, or queues `waiter` until one is pushed.
+    fn poll_pop(&self, end: End, waiter: &mut Waiter, cx: &mut Context<'_>) -> Poll<Option<T>> {
+        let mut state = self.lock();
+        let (poll, woken) = match self.pop_locked(&mut state, end) {
+            Err(PopError::Empty) => {
+                waiter.register(&mut state, cx.waker());
+                (Poll::Pending, None)
+            }
+            result => {
+                waiter.finish(&mut state);
+                let (val, woken) = result.ok().unzip();
+                (Poll::Ready(val), woken.flatten())
+            }
+        };
+        drop(state);
+        if let Some(waker) = woken {
+            waker.wake();
+        }
+        poll
+    }
+
+    /// Leaves the waiter queue, passing on a wakeup `waiter` did not use.
+    fn cancel(&self, waiter: &mut Waiter) {
+        if waiter.handle.is_some() {
+            let woken = waiter.cancel(&mut self.lock());
+            if let Some(waker) = woken {
+                waker.wake();
+            }
+        }
+    }
+}
+
+impl<T> Default for AsyncDeque<T> {
+    /// Creates an empty, unbounded `AsyncDeque<T>`.
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> fmt::Debug for AsyncDeque<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let state = self.lock();
+        f.debug_struct("AsyncDeque")
+            .field("len", &state.list.0.len())
+            .field("capacity", &self.capacity)
+            .field("closed", &state.closed)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Future behind [`AsyncDeque::push_front`] and [`AsyncDeque::push_back`].
+struct Push<'a, T> {
+    deque: &'a AsyncDeque<T>,
+    end: End,
+    val: Option<T>,
+    waiter: Waiter,
+}
+
+// The element is moved out by value, never pinned
+impl<T> Unpin for Push<'_, T> {}
+
+impl<T> Future for Push<'_, T> {
+    type Output = Result<(), PushError<T>>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+        let deque = this.deque;
+        let val = this.val.take().expect("Push polled after completion");
+        let mut state = deque.lock();
+        let full = deque.is_full(&state);
+        let (poll, woken) = match AsyncDeque::push_locked(full, &mut state, this.end, val) {
+            Err(PushError::Full(val)) => {
+                this.val = Some(val);
+                this.waiter.register(&mut state, cx.waker());
+                (Poll::Pending, None)
+            }
+            Err(err) => {
+                this.waiter.finish(&mut state);
+                (Poll::Ready(Err(err)), None)
+            }
+            Ok(woken) => {
+                this.waiter.finish(&mut state);
+                (Poll::Ready(Ok(())), woken)
+            }
+        };
+        drop(state);
+        if let Some(waker) = woken {
+            waker.wake();
+        }
+        poll
+    }
+}
+
+impl<T> Drop for Push<'_, T> {
+    fn drop(&mut self) {
+        self.deque.cancel(&mut self.waiter);
+    }
+}
+
+/// Future behind [`AsyncDeque::pop_front`] and [`AsyncDeque::pop_back`].
+struct Pop<'a, T> {
+    deque: &'a AsyncDeque<T>,
+    end: End,
+    waiter: Waiter,
+}
+
+impl<T> Future for Pop<'_, T> {
+    type Output = Option<T>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+        this.deque.poll_pop(this.end, &mut this.waiter, cx)
+    }
+}
+
+impl<T> Drop for Pop<'_, T> {
+    fn drop(&mut self) {
+        self.deque.cancel(&mut self.waiter);
+    }
+}
+
+/// A [`Stream`] popping elements from the front of an [`AsyncDeque`].
+///
+/// This `struct` is created by [`AsyncDeque::stream`]. See its
+/// documentation for more.
+pub struct PopStream<'a, T> {
+    deque: &'a AsyncDeque<T>,
+    waiter: Waiter,
+    done: bool,
+}
+
+impl<T> Stream for PopStream<'_, T> {
+    type Item = T;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<T>> {
+        let this = self.get_mut();
+        if this.done {
+            return Poll::Ready(None);
+        }
+        let poll = this.deque.poll_pop(End::Front, &mut this.waiter, cx);
+        this.done = matches!(poll, Poll::Ready(None));
+        poll
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        if self.done {
+            (0, Some(0))
+        } else {
+            (self.deque.len(), None)
+        }
+    }
+}
+
+impl<T> FusedStream for PopStream<'_, T> {
+    fn is_terminated(&self) -> bool {
+        self.done
+    }
+}
+
+impl<T> Drop for PopStream<'_, T> {
+    fn drop(&mut self) {
+        self.deque.cancel(&mut self.waiter);
+    }
+}
+
+impl<T> fmt::Debug for PopStream<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PopStream")
+            .field("deque", self.deque)
+            .field("done", &self.done)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::thread;
+
+    use futures::StreamExt;
+    use futures::executor::{LocalPool, block_on};
+    use futures::task::{ArcWake, LocalSpawnExt};
+
+    #[derive(Default)]
+    struct Flag(AtomicBool);
+
+    impl ArcWake for Flag {
+        fn wake_by_ref(arc_self: &Arc<Self>) {
+            arc_self.0.store(true, Ordering::SeqCst);
+        }
+    }
+
+    impl Flag {
+        fn take(&self) -> bool {
+            self.0.swap(false, Ordering::SeqCst)
+        }
+    }
+
+    #[test]
+    fn test_pop_waits_for_push() {
+        let deque = Rc::new(AsyncDeque::new());
+        let popped = Rc::new(RefCell::new(Vec::new()));
+        let mut pool = LocalPool::new();
+        for end in [End::Front, End::Back] {
+            let deque = Rc::clone(&deque);
+            let popped = Rc::clone(&popped);
+            pool.spawner()
+                .spawn_local(async move {
+                    let val = match end {
+                        End::Front => deque.pop_front().await,
+                        End::Back => deque.pop_back().await,
+                    };
+                    popped.borrow_mut().push(val);
+                })
+                .unwrap();
+        }
+        pool.run_until_stalled();
+        assert!(popped.borrow().is_empty());
+
+        deque.try_push_back(1).unwrap();
+        pool.run_until_stalled();
+        assert_eq!(*popped.borrow(), [Some(1)]);
+
+        deque.close();
+        pool.run_until_stalled();
+        assert_eq!(*popped.borrow(), [Some(1), None]);
+    }
+
+    #[test]
+    fn test_push_waits_for_room() {
+        let deque = Rc::new(AsyncDeque::bounded(1));
+        deque.try_push_back(1).unwrap();
+        assert_eq!(deque.try_push_front(0), Err(PushError::Full(0)));
+
+        let mut pool = LocalPool::new();
+        let pushed = {
+            let deque = Rc::clone(&deque);
+            pool.spawner()
+                .spawn_local_with_handle(async move { deque.push_front(0).await })
+                .unwrap()
+        };
+        pool.run_until_stalled();
+        assert_eq!(deque.len(), 1);
+
+        assert_eq!(deque.try_pop_back(), Ok(1));
+        assert_eq!(pool.run_until(pushed), Ok(()));
+        assert_eq!(deque.try_pop_front(), Ok(0));
+        assert_eq!(deque.try_pop_front(), Err(PopError::Empty));
+
+        deque.close();
+        assert_eq!(block_on(deque.push_back(2)), Err(PushError::Closed(2)));
+        assert_eq!(deque.try_pop_front(), Err(PopError::Closed));
+    }
+
+    #[test]
+    fn test_dropped_waiter_passes_wakeup_on() {
+        let deque = AsyncDeque::new();
+        let (first, second) = (Arc::new(Flag::default()), Arc::new(Flag::default()));
+        let mut pop_first = Box::pin(deque.pop_front());
+        let mut pop_second = Box::pin(deque.pop_front());
+        let first_waker = futures::task::waker(Arc::clone(&first));
+        let second_waker = futures::task::waker(Arc::clone(&second));
+        let mut first_cx = Context::from_waker(&first_waker);
+        let mut second_cx = Context::from_waker(&second_waker);
+        assert!(pop_first.as_mut().poll(&mut first_cx).is_pending());
+        assert!(pop_second.as_mut().poll(&mut second_cx).is_pending());
+
+        // Only the first waiter is woken, and hands the wakeup over when it
+        // is dropped without popping
+        deque.try_push_back(1).unwrap();
+        assert!(first.take());
+        assert!(!second.take());
+        drop(pop_first);
+        assert!(second.take());
+        assert_eq!(
+            pop_second.as_mut().poll(&mut second_cx),
+            Poll::Ready(Some(1))
+        );
+
+        // A waiter that is dropped before being woken just leaves the queue
+        let mut pop_third = Box::pin(deque.pop_back());
+        assert!(pop_third.as_mut().poll(&mut first_cx).is_pending());
+        drop(pop_third);
+        deque.try_push_front(2).unwrap();
+        assert!(!first.take());
+        assert!(!second.take());
+    }
+
+    #[test]
+    fn test_stream_across_threads() {
+        let deque = Arc::new(AsyncDeque::bounded(4));
+        let producers: Vec<_> = (0..3)
+            .map(|t| {
+                let deque = Arc::clone(&deque);
+                thread::spawn(move || {
+                    block_on(async {
+                        for i in 0..500 {
+                            deque.push_back(t * 500 + i).await.unwrap();
+                        }
+                    })
+                })
+            })
+            .collect();
+        let consumers: Vec<_> = (0..2)
+            .map(|_| {
+                let deque = Arc::clone(&deque);
+                thread::spawn(move || block_on(deque.stream().collect::<Vec<_>>()))
+            })
+            .collect();
+
+        producers.into_iter().for_each(|p| p.join().unwrap());
+        deque.close();
+        let mut popped: Vec<_> = consumers
+            .into_iter()
+            .flat_map(|c| c.join().unwrap())
+            .collect();
+        popped.sort();
+        assert_eq!(popped, (0..1500).collect::<Vec<_>>());
+    }
+}
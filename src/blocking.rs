@@ -0,0 +1,495 @@
+use std::error::Error;
+use std::fmt;
+use std::sync::{Condvar, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+use crate::DoublyLinkedList;
+
+/// A `DoublyLinkedList` that may be moved to another thread.
+///
+/// The list itself is `!Send` because of the `Rc` slots behind its
+/// `NodeHandle`s. The deques never create handles, so every node is owned
+/// by the list alone and moving it moves nothing but the `T`s.
+pub(crate) struct SendList<T>(pub(crate) DoublyLinkedList<T>);
+
+// SAFETY: see above, the deques only ever create the inner list empty and
+// call `push_*`, `pop_*` and `split_off` on it, none of which hands out
+// handles.
+unsafe impl<T: Send> Send for SendList<T> {}
+
+struct State<T> {
+    list: SendList<T>,
+    closed: bool,
+}
+
+/// Which end of the deque an operation acts on.
+#[derive(Clone, Copy)]
+pub(crate) enum End {
+    Front,
+    Back,
+}
+
+/// How long an operation waits for room or for an element.
+#[derive(Clone, Copy)]
+enum Wait {
+    Never,
+    Until(Instant),
+    Forever,
+}
+
+impl Wait {
+    fn timeout(timeout: Duration) -> Self {
+        // A timeout too large to represent never expires
+        Instant::now()
+            .checked_add(timeout)
+            .map_or(Wait::Forever, Wait::Until)
+    }
+}
+
+/// Error returned when an element could not be pushed. It hands the
+/// element back to the caller.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum PushError<T> {
+    /// The deque is at capacity, and stayed there for the whole timeout.
+    Full(T),
+    /// The deque has been closed.
+    Closed(T),
+}
+
+impl<T> PushError<T> {
+    /// Returns the element that could not be pushed.
+    pub fn into_inner(self) -> T {
+        match self {
+            PushError::Full(val) | PushError::Closed(val) => val,
+        }
+    }
+}
+
+impl<T> fmt::Debug for PushError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PushError::Full(_) => f.write_str("Full(..)"),
+            PushError::Closed(_) => f.write_str("Closed(..)"),
+        }
+    }
+}
+
+impl<T> fmt::Display for PushError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PushError::Full(_) => f.write_str("pushing onto a full deque"),
+            PushError::Closed(_) => f.write_str("pushing onto a closed deque"),
+        }
+    }
+}
+
+impl<T> Error for PushError<T> {}
+
+/// Error returned when no element could be popped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PopError {
+    /// The deque is empty, and stayed empty for the whole timeout.
+    Empty,
+    /// The deque has been closed and every element has been popped.
+    Closed,
+}
+
+impl fmt::Display for PopError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PopError::Empty => f.write_str("popping from an empty deque"),
+            PopError::Closed => f.write_str("popping from a closed and empty deque"),
+        }
+    }
+}
+
+impl Error for PopError {}
+
+/// A `DoublyLinkedList` behind a mutex, for handing work between threads.
+///
+/// Pushing onto a deque created with [`BlockingDeque::bounded`] blocks
+/// while it is full, popping blocks while it is empty. Every blocking
+/// operation also comes as a `try_*` variant that returns immediately and
+/// a `*_timeout` variant that gives up after a while.
+///
+/// [`BlockingDeque::close`] wakes up all waiting threads: pushes fail from
+/// then on, while pops keep returning the remaining elements until the
+/// deque is drained.
+///
+/// ```
+/// use std::sync::Arc;
+/// use std::thread;
+///
+/// use dll_rs::BlockingDeque;
+///
+/// let deque = Arc::new(BlockingDeque::bounded(2));
+/// let consumer = {
+///     let deque = Arc::clone(&deque);
+///     thread::spawn(move || {
+///         let mut sum = 0;
+///         while let Some(val) = deque.pop_front() {
+///             sum += val;
+///         }
+///         sum
+///     })
+/// };
+/// for i in 1..=10 {
+///     deque.push_back(i).unwrap();
+/// }
+/// deque.close();
+/// assert_eq!(consumer.join().unwrap(), 55);
+/// ```
+pub struct BlockingDeque<T> {
+    state: Mutex<State<T>>,
+    not_empty: Condvar,
+    not_full: Condvar,
+    capacity: Option<usize>,
+}
+
+impl<T> BlockingDeque<T> {
+    /// Creates a new, empty deque without a capacity bound. Pushes never
+    /// block.
+    pub fn new() -> Self {
+        Self::with_bound(None)
+    }
+
+    /// Creates a new, empty deque holding at most `capacity` elements.
+    ///
+    /// # Panics
+    /// Panics if `capacity` is 0, as nothing could ever be pushed.
+    pub fn bounded(capacity: usize) -> Self {
+        assert!(capacity > 0, "capacity of a BlockingDeque must be positive");
+        Self::with_bound(Some(capacity))
+    }
+
+    fn with_bound(capacity: Option<usize>) -> Self {
+        BlockingDeque {
+            state: Mutex::new(State {
+                list: SendList(DoublyLinkedList::new()),
+                closed: false,
+            }),
+            not_empty: Condvar::new(),
+            not_full: Condvar::new(),
+            capacity,
+        }
+    }
+
+    /// Returns the capacity bound, or `None` if the deque is unbounded.
+    pub fn capacity(&self) -> Option<usize> {
+        self.capacity
+    }
+
+    /// Returns the number of elements in the deque.
+    pub fn len(&self) -> usize {
+        self.lock().list.0.len()
+    }
+
+    /// Returns true if the deque contains no elements.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns true if [`BlockingDeque::close`] has been called.
+    pub fn is_closed(&self) -> bool {
+        self.lock().closed
+    }
+
+    /// Closes the deque and wakes up every blocked thread. Elements that
+    /// are still queued can be popped as usual.
+    pub fn close(&self) {
+        self.lock().closed = true;
+        self.not_empty.notify_all();
+        self.not_full.notify_all();
+    }
+
+    /// Adds an element to the front, blocking while the deque is full.
+    pub fn push_front(&self, val: T) -> Result<(), PushError<T>> {
+        self.push(End::Front, val, Wait::Forever)
+    }
+
+    /// Adds an element to the back, blocking while the deque is full.
+    pub fn push_back(&self, val: T) -> Result<(), PushError<T>> {
+        self.push(End::Back, val, Wait::Forever)
+    }
+
+    /// Adds an element to the front, or fails right away if the deque is full.
+    pub fn try_push_front(&self, val: T) -> Result<(), PushError<T>> {
+        self.push(End::Front, val, Wait::Never)
+    }
+
+    /// Adds an element to the back, or fails right away if the deque is full.
+    pub fn try_push_back(&self, val: T) -> Result<(), PushError<T>> {
+        self.push(End::Back, val, Wait::Never)
+    }
+
+    /// Adds an element to the front, waiting at most `timeout` for room.
+    pub fn push_front_timeout(&self, val: T, timeout: Duration) -> Result<(), PushError<T>> {
+        self.push(End::Front, val, Wait::timeout(timeout))
+    }
+
+    /// Adds an element to the back, waiting at most `timeout` for room.
+    pub fn push_back_timeout(&self, val: T, timeout: Duration) -> Result<(), PushError<T>> {
+        self.push(End::Back, val, Wait::timeout(timeout))
+    }
+
+    /// Removes the first element, blocking while the deque is empty.
+    /// Returns `None` once the deque is closed and drained.
+    pub fn pop_front(&self) -> Option<T> {
+        self.pop(End::Front, Wait::Forever).ok()
+    }
+
+    /// Removes the last element, blocking while the deque is empty.
+    /// Returns `None` once the deque is closed and drained.
+    pub fn pop_back(&self) -> Option<T> {
+        self.pop(End::Back, Wait::Forever).ok()
+    }
+
+    /// Removes the first element, or fails right away if the deque is empty.
+    pub fn try_pop_front(&self) -> Result<T, PopError> {
+        self.pop(End::Front, Wait::Never)
+    }
+
+    /// Removes the last element, or fails right away if the deque is empty.
+    pub fn try_pop_back(&self) -> Result<T, PopError> {
+        self.pop(End::Back, Wait::Never)
+    }
+
+    /// Removes the first element, waiting at most `timeout` for one.
+    pub fn pop_front_timeout(&self, timeout: Duration) -> Result<T, PopError> {
+        self.pop(End::Front, Wait::timeout(timeout))
+    }
+
+    /// Removes the last element, waiting at most `timeout` for one.
+    pub fn pop_back_timeout(&self, timeout: Duration) -> Result<T, PopError> {
+        self.pop(End::Back, Wait::timeout(timeout))
+    }
+
+    /// Takes the back half of the elements, rounded up, without blocking.
+    ///
+    /// Meant for work stealing: each worker pops from the front of its own
+    /// deque, and an idle worker steals a batch from the back of a busy
+    /// one, away from where its owner is working.
+    /// ```
+    /// use dll_rs::{BlockingDeque, DoublyLinkedList};
+    ///
+    /// let deque = BlockingDeque::new();
+    /// (1..=5).for_each(|i| deque.push_back(i).unwrap());
+    /// assert_eq!(deque.steal_back(), DoublyLinkedList::from([3, 4, 5]));
+    /// assert_eq!(deque.len(), 2);
+    /// ```
+    pub fn steal_back(&self) -> DoublyLinkedList<T> {
+        let stolen = {
+            let mut state = self.lock();
+            let list = &mut state.list.0;
+            list.split_off(list.len() / 2)
+        };
+        if !stolen.is_empty() {
+            self.not_full.notify_all();
+        }
+        stolen
+    }
+
+    fn lock(&self) -> MutexGuard<'_, State<T>> {
+        // Every critical section leaves the list in a consistent state
+        // before anything that could panic
+        self.state
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn is_full(&self, state: &State<T>) -> bool {
+        self.capacity
+            .is_some_and(|capacity| state.list.0.len() >= capacity)
+    }
+
+    /// Waits on `condvar` while `blocked` returns true and the deque is open.
+    /// Returns `None` if `wait` ran out first.
+    fn wait_while<'a>(
+        &self,
+        mut state: MutexGuard<'a, State<T>>,
+        condvar: &Condvar,
+        wait: Wait,
+        mut blocked: impl FnMut(&State<T>) -> bool,
+    ) -> Option<MutexGuard<'a, State<T>>> {
+        loop {
+            if state.closed || !blocked(&state) {
+                return Some(state);
+            }
+            state = match wait {
+                Wait::Never => return None,
+                Wait::Forever => condvar
+                    .wait(state)
+                    .unwrap_or_else(|poisoned| poisoned.into_inner()),
+                Wait::Until(deadline) => {
+                    let timeout = deadline.checked_duration_since(Instant::now())?;
+                    match condvar.wait_timeout(state, timeout) {
+                        Ok((state, _)) => state,
+                        Err(poisoned) => poisoned.into_inner().0,
+                    }
+                }
+            };
+        }
+    }
+
+    fn push(&self, end: End, val: T, wait: Wait) -> Result<(), PushError<T>> {
+        let state = self.lock();
+        let Some(mut state) = self.wait_while(state, &self.not_full, wait, |s| self.is_full(s))
+        else {
+            return Err(PushError::Full(val));
+        };
+        if state.closed {
+            return Err(PushError::Closed(val));
+        }
+        match end {
+            End::Front => state.list.0.push_front(val),
+            End::Back => state.list.0.push_back(val),
+        }
+        drop(state);
+        self.not_empty.notify_one();
+        Ok(())
+    }
+
+    fn pop(&self, end: End, wait: Wait) -> Result<T, PopError> {
+        let state = self.lock();
+        let mut state = self
+            .wait_while(state, &self.not_empty, wait, |s| s.list.0.is_empty())
+            .ok_or(PopError::Empty)?;
+        let val = match end {
+            End::Front => state.list.0.pop_front(),
+            End::Back => state.list.0.pop_back(),
+        }
+        .ok_or(PopError::Closed)?;
+        drop(state);
+        if self.capacity.is_some() {
+            self.not_full.notify_one();
+        }
+        Ok(val)
+    }
+}
+
+impl<T> Default for BlockingDeque<T> {
+    /// Creates an empty, unbounded `BlockingDeque<T>`.
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> fmt::Debug for BlockingDeque<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let state = self.lock();
+        f.debug_struct("BlockingDeque")
+            .field("len", &state.list.0.len())
+            .field("capacity", &self.capacity)
+            .field("closed", &state.closed)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use std::thread;
+
+    #[test]
+    fn test_try_variants_respect_capacity() {
+        let deque = BlockingDeque::bounded(2);
+        assert_eq!(deque.try_pop_front(), Err(PopError::Empty));
+        deque.try_push_back(2).unwrap();
+        deque.try_push_front(1).unwrap();
+        assert_eq!(deque.try_push_back(3), Err(PushError::Full(3)));
+        assert_eq!(
+            deque.push_back_timeout(3, Duration::from_millis(10)),
+            Err(PushError::Full(3))
+        );
+        assert_eq!(deque.try_pop_back(), Ok(2));
+        assert_eq!(deque.pop_front_timeout(Duration::from_millis(10)), Ok(1));
+        assert_eq!(
+            deque.pop_back_timeout(Duration::from_millis(10)),
+            Err(PopError::Empty)
+        );
+    }
+
+    #[test]
+    fn test_close_wakes_waiters_and_drains() {
+        let deque = Arc::new(BlockingDeque::bounded(1));
+        deque.push_back(1).unwrap();
+        let pusher = {
+            let deque = Arc::clone(&deque);
+            thread::spawn(move || deque.push_back(2))
+        };
+        thread::sleep(Duration::from_millis(20));
+        deque.close();
+        assert_eq!(pusher.join().unwrap(), Err(PushError::Closed(2)));
+        assert_eq!(
+            deque.try_push_front(3).map_err(PushError::into_inner),
+            Err(3)
+        );
+
+        // Queued elements survive the close
+        assert_eq!(deque.pop_front(), Some(1));
+        assert_eq!(deque.pop_front(), None);
+        assert_eq!(deque.try_pop_back(), Err(PopError::Closed));
+
+        let deque = Arc::new(BlockingDeque::<i32>::new());
+        let popper = {
+            let deque = Arc::clone(&deque);
+            thread::spawn(move || deque.pop_back())
+        };
+        thread::sleep(Duration::from_millis(20));
+        deque.close();
+        assert_eq!(popper.join().unwrap(), None);
+    }
+
+    #[test]
+    fn test_producers_and_consumers() {
+        let deque = Arc::new(BlockingDeque::bounded(4));
+        let producers: Vec<_> = (0..3)
+            .map(|t| {
+                let deque = Arc::clone(&deque);
+                thread::spawn(move || {
+                    for i in 0..500 {
+                        deque.push_back(t * 500 + i).unwrap();
+                    }
+                })
+            })
+            .collect();
+        let consumers: Vec<_> = (0..2)
+            .map(|_| {
+                let deque = Arc::clone(&deque);
+                thread::spawn(move || {
+                    let mut popped = Vec::new();
+                    while let Some(val) = deque.pop_front() {
+                        assert!(deque.len() <= 4);
+                        popped.push(val);
+                    }
+                    popped
+                })
+            })
+            .collect();
+
+        producers.into_iter().for_each(|p| p.join().unwrap());
+        deque.close();
+        let mut popped: Vec<_> = consumers
+            .into_iter()
+            .flat_map(|c| c.join().unwrap())
+            .collect();
+        popped.sort();
+        assert_eq!(popped, (0..1500).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_steal_back_unblocks_pushers() {
+        let deque = Arc::new(BlockingDeque::bounded(3));
+        (0..3).for_each(|i| deque.push_back(i).unwrap());
+        let pusher = {
+            let deque = Arc::clone(&deque);
+            thread::spawn(move || deque.push_front(-1))
+        };
+        assert_eq!(deque.steal_back(), DoublyLinkedList::from([1, 2]));
+        pusher.join().unwrap().unwrap();
+        assert_eq!(deque.steal_back(), DoublyLinkedList::from([0]));
+        assert_eq!(deque.steal_back(), DoublyLinkedList::from([-1]));
+        assert!(deque.steal_back().is_empty());
+    }
+}
@@ -0,0 +1,112 @@
+//! Cache replacement policies built on `DoublyLinkedList` segments.
+//!
+//! Every policy implements the common [`Cache`] trait and counts its hits,
+//! misses and evictions in a [`CacheStats`], so policies can be swapped
+//! behind a `Box<dyn Cache<K, V>>` and compared on the same workload.
+
+mod arc;
+mod lfu;
+mod slru;
+mod two_queue;
+
+pub use arc::ArcCache;
+pub use lfu::LfuCache;
+pub use slru::SlruCache;
+pub use two_queue::TwoQueueCache;
+
+/// Hit, miss and eviction counters of a cache.
+///
+/// Lookups through [`Cache::get`] count as a hit or a miss, while
+/// [`Cache::peek`] is not counted. Evictions are entries the cache dropped
+/// to stay within its capacity.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    pub evictions: u64,
+}
+
+impl CacheStats {
+    /// Returns the share of lookups that were hits, or 0 before the first
+    /// lookup.
+    pub fn hit_ratio(&self) -> f64 {
+        match self.hits + self.misses {
+            0 => 0.0,
+            lookups => self.hits as f64 / lookups as f64,
+        }
+    }
+
+    pub(crate) fn record_lookup(&mut self, hit: bool) {
+        if hit {
+            self.hits += 1;
+        } else {
+            self.misses += 1;
+        }
+    }
+}
+
+/// The operations shared by every cache policy.
+///
+/// ```
+/// use dll_rs::cache::{ArcCache, Cache, LfuCache, SlruCache, TwoQueueCache};
+/// use dll_rs::LruCache;
+///
+/// let mut caches: Vec<Box<dyn Cache<u32, u32>>> = vec![
+///     Box::new(LruCache::new(4)),
+///     Box::new(LfuCache::new(4)),
+///     Box::new(TwoQueueCache::new(4)),
+///     Box::new(ArcCache::new(4)),
+///     Box::new(SlruCache::new(4)),
+/// ];
+/// for cache in &mut caches {
+///     for key in [1, 2, 1, 3, 1, 4, 5, 6, 1] {
+///         if cache.get(&key).is_none() {
+///             cache.put(key, key * 10);
+///         }
+///     }
+///     assert!(cache.len() <= 4);
+///     assert_eq!(cache.stats().hits + cache.stats().misses, 9);
+/// }
+/// ```
+pub trait Cache<K, V> {
+    /// Returns a reference to the value of `key`, recording the access
+    /// with the policy and in the stats.
+    fn get(&mut self, key: &K) -> Option<&V>;
+
+    /// Returns a reference to the value of `key` without recording the
+    /// access.
+    fn peek(&self, key: &K) -> Option<&V>;
+
+    /// Returns true if the cache holds a value for `key`, without recording
+    /// the access.
+    fn contains(&self, key: &K) -> bool {
+        self.peek(key).is_some()
+    }
+
+    /// Inserts a value for `key`, evicting entries if the cache is full.
+    /// Returns the previous value of `key`, if there was one.
+    fn put(&mut self, key: K, val: V) -> Option<V>;
+
+    /// Removes the entry for `key` and returns its value.
+    fn pop(&mut self, key: &K) -> Option<V>;
+
+    /// Removes all entries, without counting them as evictions.
+    fn clear(&mut self);
+
+    /// Returns the number of entries holding a value.
+    fn len(&self) -> usize;
+
+    /// Returns true if the cache holds no values.
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the maximum number of entries holding a value.
+    fn capacity(&self) -> usize;
+
+    /// Returns the hit, miss and eviction counters.
+    fn stats(&self) -> CacheStats;
+
+    /// Sets all counters back to 0.
+    fn reset_stats(&mut self);
+}
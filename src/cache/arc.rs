@@ -0,0 +1,291 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::hash::Hash;
+
+use super::{Cache, CacheStats};
+use crate::{DoublyLinkedList, NodeHandle};
+
+/// Where the entry of a key lives.
+enum Slot<K, V> {
+    Recent(NodeHandle<(K, V)>),
+    Frequent(NodeHandle<(K, V)>),
+    RecentGhost(NodeHandle<K>),
+    FrequentGhost(NodeHandle<K>),
+}
+
+/// An adaptive replacement cache (ARC), which balances recency and
+/// frequency by itself.
+///
+/// Entries used once live in an LRU `recent` list and entries used again in
+/// an LRU `frequent` list. Keys evicted from either list are remembered in a
+/// ghost list of their own. Putting a key remembered as a recent ghost shows
+/// that `recent` is too small and moves the target size of `recent` up, a
+/// frequent ghost moves it down. Entries and ghosts together never number
+/// more than twice the capacity.
+///
+/// ```
+/// use dll_rs::cache::{ArcCache, Cache};
+///
+/// let mut cache = ArcCache::new(2);
+/// cache.put(0, "hot");
+/// cache.get(&0);
+///
+/// // 0 has been used twice, so a scan only churns `recent`
+/// (1..100).for_each(|key| { cache.put(key, "scan"); });
+/// assert_eq!(cache.get(&0), Some(&"hot"));
+/// ```
+pub struct ArcCache<K, V> {
+    recent: DoublyLinkedList<(K, V)>,   // Most recently used first
+    frequent: DoublyLinkedList<(K, V)>, // Most recently used first
+    recent_ghosts: DoublyLinkedList<K>, // Most recently evicted first
+    frequent_ghosts: DoublyLinkedList<K>,
+    map: HashMap<K, Slot<K, V>>,
+    capacity: usize,
+    recent_target: usize, // How many of the entries `recent` should hold
+    stats: CacheStats,
+}
+
+impl<K: Hash + Eq + Clone, V> ArcCache<K, V> {
+    /// Creates a new, empty cache holding at most `capacity` entries.
+    ///
+    /// # Panics
+    /// Panics if `capacity` is 0, as nothing could ever be cached.
+    pub fn new(capacity: usize) -> Self {
+        assert!(capacity > 0, "capacity of an ArcCache must be positive");
+        ArcCache {
+            recent: DoublyLinkedList::new(),
+            frequent: DoublyLinkedList::new(),
+            recent_ghosts: DoublyLinkedList::new(),
+            frequent_ghosts: DoublyLinkedList::new(),
+            map: HashMap::new(),
+            capacity,
+            recent_target: 0,
+            stats: CacheStats::default(),
+        }
+    }
+
+    /// Returns how many entries the cache currently aims to keep in its
+    /// `recent` list, between 0 and the capacity.
+    pub fn recent_target(&self) -> usize {
+        self.recent_target
+    }
+
+    /// Moves the entry behind a `Recent` or `Frequent` slot to the front of
+    /// `frequent`.
+    fn promote(&mut self, key: &K) -> Option<&mut (K, V)> {
+        let slot = self.map.get_mut(key)?;
+        let handle = match slot {
+            Slot::Recent(handle) => {
+                let entry = self.recent.remove_by_handle(handle).unwrap();
+                let handle = self.frequent.push_front_with_handle(entry);
+                *slot = Slot::Frequent(handle.clone());
+                handle
+            }
+            Slot::Frequent(handle) => {
+                self.frequent.move_to_front(handle).unwrap();
+                handle.clone()
+            }
+            Slot::RecentGhost(_) | Slot::FrequentGhost(_) => return None,
+        };
+        Some(self.frequent.get_mut_by_handle(&handle).unwrap())
+    }
+
+    /// Evicts an entry to the ghosts if the cache is full. Takes it from
+    /// `recent` while that is over its target, from `frequent` otherwise.
+    fn replace(&mut self, frequent_ghost_hit: bool) {
+        if self.len() < self.capacity {
+            return;
+        }
+        let recent_len = self.recent.len();
+        let from_recent = recent_len > 0
+            && (recent_len > self.recent_target
+                || (frequent_ghost_hit && recent_len == self.recent_target));
+        let (key, slot) = if from_recent || self.frequent.is_empty() {
+            let (key, _) = self.recent.pop_back().unwrap();
+            let handle = self.recent_ghosts.push_front_with_handle(key.clone());
+            (key, Slot::RecentGhost(handle))
+        } else {
+            let (key, _) = self.frequent.pop_back().unwrap();
+            let handle = self.frequent_ghosts.push_front_with_handle(key.clone());
+            (key, Slot::FrequentGhost(handle))
+        };
+        self.map.insert(key, slot);
+        self.stats.evictions += 1;
+    }
+}
+
+impl<K: Hash + Eq + Clone, V> Cache<K, V> for ArcCache<K, V> {
+    fn get(&mut self, key: &K) -> Option<&V> {
+        let hit = self.contains(key);
+        self.stats.record_lookup(hit);
+        self.promote(key).map(|(_, val)| &*val)
+    }
+
+    fn peek(&self, key: &K) -> Option<&V> {
+        match self.map.get(key)? {
+            Slot::Recent(handle) => Some(&self.recent.get_by_handle(handle).unwrap().1),
+            Slot::Frequent(handle) => Some(&self.frequent.get_by_handle(handle).unwrap().1),
+            Slot::RecentGhost(_) | Slot::FrequentGhost(_) => None,
+        }
+    }
+
+    fn put(&mut self, key: K, val: V) -> Option<V> {
+        if let Some(entry) = self.promote(&key) {
+            return Some(std::mem::replace(&mut entry.1, val));
+        }
+        let (recent_ghosts, frequent_ghosts) =
+            (self.recent_ghosts.len(), self.frequent_ghosts.len());
+        match self.map.remove(&key) {
+            Some(Slot::RecentGhost(handle)) => {
+                // `recent` evicted it too early
+                let delta = (frequent_ghosts / recent_ghosts).max(1);
+                self.recent_target = (self.recent_target + delta).min(self.capacity);
+                self.recent_ghosts.remove_by_handle(&handle).unwrap();
+                self.replace(false);
+                let handle = self.frequent.push_front_with_handle((key.clone(), val));
+                self.map.insert(key, Slot::Frequent(handle));
+            }
+            Some(Slot::FrequentGhost(handle)) => {
+                // `frequent` evicted it too early
+                let delta = (recent_ghosts / frequent_ghosts).max(1);
+                self.recent_target = self.recent_target.saturating_sub(delta);
+                self.frequent_ghosts.remove_by_handle(&handle).unwrap();
+                self.replace(true);
+                let handle = self.frequent.push_front_with_handle((key.clone(), val));
+                self.map.insert(key, Slot::Frequent(handle));
+            }
+            _ => {
+                if self.recent.len() + recent_ghosts == self.capacity {
+                    // `recent` and its ghosts have no room left
+                    if let Some(forgotten) = self.recent_ghosts.pop_back() {
+                        self.map.remove(&forgotten);
+                        self.replace(false);
+                    } else {
+                        let (evicted, _) = self.recent.pop_back().unwrap();
+                        self.map.remove(&evicted);
+                        self.stats.evictions += 1;
+                    }
+                } else {
+                    if self.len() + recent_ghosts + frequent_ghosts == 2 * self.capacity {
+                        let forgotten = self.frequent_ghosts.pop_back().unwrap();
+                        self.map.remove(&forgotten);
+                    }
+                    self.replace(false);
+                }
+                let handle = self.recent.push_front_with_handle((key.clone(), val));
+                self.map.insert(key, Slot::Recent(handle));
+            }
+        }
+        None
+    }
+
+    fn pop(&mut self, key: &K) -> Option<V> {
+        match self.map.remove(key)? {
+            Slot::Recent(handle) => Some(self.recent.remove_by_handle(&handle).unwrap().1),
+            Slot::Frequent(handle) => Some(self.frequent.remove_by_handle(&handle).unwrap().1),
+            Slot::RecentGhost(handle) => {
+                self.recent_ghosts.remove_by_handle(&handle).unwrap();
+                None
+            }
+            Slot::FrequentGhost(handle) => {
+                self.frequent_ghosts.remove_by_handle(&handle).unwrap();
+                None
+            }
+        }
+    }
+
+    fn clear(&mut self) {
+        self.map.clear();
+        self.recent.clear();
+        self.frequent.clear();
+        self.recent_ghosts.clear();
+        self.frequent_ghosts.clear();
+        self.recent_target = 0;
+    }
+
+    fn len(&self) -> usize {
+        self.recent.len() + self.frequent.len()
+    }
+
+    fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    fn stats(&self) -> CacheStats {
+        self.stats
+    }
+
+    fn reset_stats(&mut self) {
+        self.stats = CacheStats::default();
+    }
+}
+
+impl<K, V> fmt::Debug for ArcCache<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ArcCache")
+            .field("recent", &self.recent.len())
+            .field("frequent", &self.frequent.len())
+            .field("recent_target", &self.recent_target)
+            .field("capacity", &self.capacity)
+            .field("stats", &self.stats)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ghost_hits_adapt_recent_target() {
+        let mut cache = ArcCache::new(2);
+        cache.put(1, "a");
+        cache.put(2, "b");
+        assert_eq!(cache.get(&1), Some(&"a"));
+        assert_eq!(cache.frequent.len(), 1);
+
+        // 2 and then 3 are evicted from `recent` to its ghosts
+        cache.put(3, "c");
+        cache.put(4, "d");
+        assert_eq!(cache.peek(&2), None);
+        assert_eq!(cache.recent_ghosts.len(), 1);
+        assert_eq!(cache.len(), 2);
+
+        // Putting a recent ghost again grows the target of `recent`
+        cache.put(3, "C");
+        assert_eq!(cache.recent_target(), 1);
+        assert_eq!(cache.peek(&3), Some(&"C"));
+        assert!(cache.frequent_ghosts.len() + cache.recent_ghosts.len() <= 2);
+
+        // And putting a frequent ghost shrinks it
+        let frequent_ghost = *cache.frequent_ghosts.front().unwrap();
+        cache.put(frequent_ghost, "x");
+        assert_eq!(cache.recent_target(), 0);
+        assert_eq!(cache.len(), 2);
+    }
+
+    #[test]
+    fn test_ghosts_stay_bounded() {
+        let mut cache = ArcCache::new(4);
+        for round in 0..3 {
+            for key in 0..10 {
+                cache.put(key, round);
+                if key % 2 == 0 {
+                    cache.get(&key);
+                }
+                assert!(cache.len() <= 4);
+                assert!(cache.len() + cache.recent_ghosts.len() + cache.frequent_ghosts.len() <= 8);
+                assert!(cache.recent.len() + cache.recent_ghosts.len() <= 4);
+            }
+        }
+        assert_eq!(
+            cache.map.len(),
+            cache.len() + cache.recent_ghosts.len() + cache.frequent_ghosts.len()
+        );
+
+        assert_eq!(cache.pop(&9), Some(2));
+        cache.clear();
+        assert!(cache.is_empty());
+        assert_eq!(cache.recent_target(), 0);
+    }
+}
@@ -0,0 +1,237 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::hash::Hash;
+
+use super::{Cache, CacheStats};
+use crate::{DoublyLinkedList, NodeHandle};
+
+/// A least frequently used cache.
+///
+/// Entries are grouped into one `DoublyLinkedList` per use count, most
+/// recently used first, so the entry evicted is the least recently used
+/// among the least frequently used ones. Every operation is O(1), apart from
+/// [`Cache::pop`] emptying the least frequent list, which looks for the next
+/// one among the use counts present.
+///
+/// ```
+/// use dll_rs::cache::{Cache, LfuCache};
+///
+/// let mut cache = LfuCache::new(2);
+/// cache.put("a", 1);
+/// cache.put("b", 2);
+/// cache.get(&"a");
+///
+/// // "b" was used once, "a" twice
+/// cache.put("c", 3);
+/// assert!(!cache.contains(&"b"));
+/// assert_eq!(cache.frequency(&"a"), Some(2));
+/// ```
+pub struct LfuCache<K, V> {
+    // Entries by use count
+    buckets: HashMap<u64, DoublyLinkedList<(K, V)>>,
+    map: HashMap<K, (u64, NodeHandle<(K, V)>)>,
+    min_freq: u64, // Lowest use count with a bucket, if there is any
+    len: usize,
+    capacity: usize,
+    stats: CacheStats,
+}
+
+impl<K: Hash + Eq + Clone, V> LfuCache<K, V> {
+    /// Creates a new, empty cache holding at most `capacity` entries.
+    ///
+    /// # Panics
+    /// Panics if `capacity` is 0, as nothing could ever be cached.
+    pub fn new(capacity: usize) -> Self {
+        assert!(capacity > 0, "capacity of an LfuCache must be positive");
+        LfuCache {
+            buckets: HashMap::new(),
+            map: HashMap::with_capacity(capacity),
+            min_freq: 0,
+            len: 0,
+            capacity,
+            stats: CacheStats::default(),
+        }
+    }
+
+    /// Returns how often `key` has been put or looked up since it was
+    /// inserted.
+    pub fn frequency(&self, key: &K) -> Option<u64> {
+        self.map.get(key).map(|&(freq, _)| freq)
+    }
+
+    /// Unlinks the entry with the given use count, dropping its bucket if
+    /// it became empty.
+    fn unlink(&mut self, freq: u64, handle: &NodeHandle<(K, V)>) -> (K, V) {
+        let bucket = self.buckets.get_mut(&freq).unwrap();
+        let entry = bucket.remove_by_handle(handle).unwrap();
+        if bucket.is_empty() {
+            self.buckets.remove(&freq);
+        }
+        self.len -= 1;
+        entry
+    }
+
+    /// Links an entry into the bucket of `freq` and indexes it.
+    fn link(&mut self, freq: u64, entry: (K, V)) -> &mut (K, V) {
+        let key = entry.0.clone();
+        let bucket = self.buckets.entry(freq).or_default();
+        let handle = bucket.push_front_with_handle(entry);
+        self.len += 1;
+        let (_, handle) = self.map.entry(key).insert_entry((freq, handle)).into_mut();
+        bucket.get_mut_by_handle(handle).unwrap()
+    }
+
+    /// Counts a use of `key` by moving it to the next bucket.
+    fn touch(&mut self, key: &K) -> Option<&mut (K, V)> {
+        let (freq, handle) = self.map.get(key)?.clone();
+        let entry = self.unlink(freq, &handle);
+        if self.min_freq == freq && !self.buckets.contains_key(&freq) {
+            self.min_freq = freq + 1;
+        }
+        Some(self.link(freq + 1, entry))
+    }
+}
+
+impl<K: Hash + Eq + Clone, V> Cache<K, V> for LfuCache<K, V> {
+    fn get(&mut self, key: &K) -> Option<&V> {
+        let hit = self.contains(key);
+        self.stats.record_lookup(hit);
+        self.touch(key).map(|(_, val)| &*val)
+    }
+
+    fn peek(&self, key: &K) -> Option<&V> {
+        let (freq, handle) = self.map.get(key)?;
+        Some(&self.buckets[freq].get_by_handle(handle).unwrap().1)
+    }
+
+    fn contains(&self, key: &K) -> bool {
+        self.map.contains_key(key)
+    }
+
+    fn put(&mut self, key: K, val: V) -> Option<V> {
+        if let Some(entry) = self.touch(&key) {
+            return Some(std::mem::replace(&mut entry.1, val));
+        }
+        if self.len == self.capacity {
+            let bucket = self.buckets.get_mut(&self.min_freq).unwrap();
+            let (evicted, _) = bucket.pop_back().unwrap();
+            if bucket.is_empty() {
+                self.buckets.remove(&self.min_freq);
+            }
+            self.map.remove(&evicted);
+            self.len -= 1;
+            self.stats.evictions += 1;
+        }
+        self.link(1, (key, val));
+        self.min_freq = 1;
+        None
+    }
+
+    fn pop(&mut self, key: &K) -> Option<V> {
+        let (freq, handle) = self.map.remove(key)?;
+        let (_, val) = self.unlink(freq, &handle);
+        if self.min_freq == freq && !self.buckets.contains_key(&freq) {
+            self.min_freq = self.buckets.keys().copied().min().unwrap_or(0);
+        }
+        Some(val)
+    }
+
+    fn clear(&mut self) {
+        self.buckets.clear();
+        self.map.clear();
+        self.min_freq = 0;
+        self.len = 0;
+    }
+
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    fn stats(&self) -> CacheStats {
+        self.stats
+    }
+
+    fn reset_stats(&mut self) {
+        self.stats = CacheStats::default();
+    }
+}
+
+impl<K, V> fmt::Debug for LfuCache<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LfuCache")
+            .field("len", &self.len)
+            .field("capacity", &self.capacity)
+            .field("stats", &self.stats)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_evicts_least_frequent_then_least_recent() {
+        let mut cache = LfuCache::new(3);
+        cache.put(1, "a");
+        cache.put(2, "b");
+        cache.put(3, "c");
+        assert_eq!(cache.get(&1), Some(&"a"));
+        assert_eq!(cache.get(&1), Some(&"a"));
+        assert_eq!(cache.put(2, "B"), Some("b"));
+        assert_eq!(cache.get(&4), None);
+
+        // 3 is the only entry used once
+        cache.put(4, "d");
+        assert!(!cache.contains(&3));
+        assert_eq!(cache.frequency(&1), Some(3));
+        assert_eq!(cache.frequency(&2), Some(2));
+        assert_eq!(cache.frequency(&4), Some(1));
+
+        // Among the entries used twice, 2 is the least recent
+        cache.get(&4);
+        cache.put(5, "e");
+        assert!(!cache.contains(&2));
+        assert!(cache.contains(&5));
+        assert_eq!(cache.peek(&4), Some(&"d"));
+        assert_eq!(
+            cache.stats(),
+            CacheStats {
+                hits: 3,
+                misses: 1,
+                evictions: 2
+            }
+        );
+    }
+
+    #[test]
+    fn test_pop_finds_next_least_frequent() {
+        let mut cache = LfuCache::new(2);
+        cache.put("a", 1);
+        cache.put("b", 2);
+        cache.get(&"b");
+        cache.get(&"b");
+        assert_eq!(cache.pop(&"a"), Some(1));
+        assert_eq!(cache.pop(&"a"), None);
+        assert_eq!(cache.len(), 1);
+
+        // With "a" gone, "b" (used three times) is evicted before "c"
+        // (used four times)
+        cache.put("c", 3);
+        cache.get(&"c");
+        cache.get(&"c");
+        cache.get(&"c");
+        cache.put("d", 4);
+        assert_eq!(cache.peek(&"b"), None);
+        assert_eq!(cache.peek(&"c"), Some(&3));
+
+        cache.clear();
+        assert!(cache.is_empty());
+        cache.put("e", 5);
+        assert_eq!(cache.frequency(&"e"), Some(1));
+    }
+}
@@ -0,0 +1,249 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::hash::Hash;
+
+use super::{Cache, CacheStats};
+use crate::{DoublyLinkedList, NodeHandle};
+
+/// Which segment the entry of a key lives in.
+enum Slot<K, V> {
+    Probation(NodeHandle<(K, V)>),
+    Protected(NodeHandle<(K, V)>),
+}
+
+/// A segmented LRU cache.
+///
+/// New entries start out in an LRU `probation` segment, and are promoted to
+/// an LRU `protected` segment when they are used again. When `protected`
+/// outgrows its share of the capacity its least recently used entry is
+/// demoted back to `probation`, and entries are only ever evicted from
+/// `probation`, unless it is empty.
+///
+/// ```
+/// use dll_rs::cache::{Cache, SlruCache};
+///
+/// let mut cache = SlruCache::new(4);
+/// cache.put(0, "hot");
+/// cache.get(&0);
+///
+/// // 0 is protected, so a scan only churns `probation`
+/// (1..100).for_each(|key| { cache.put(key, "scan"); });
+/// assert_eq!(cache.get(&0), Some(&"hot"));
+/// ```
+pub struct SlruCache<K, V> {
+    probation: DoublyLinkedList<(K, V)>, // Most recently used first
+    protected: DoublyLinkedList<(K, V)>, // Most recently used first
+    map: HashMap<K, Slot<K, V>>,
+    capacity: usize,
+    protected_capacity: usize,
+    stats: CacheStats,
+}
+
+impl<K: Hash + Eq + Clone, V> SlruCache<K, V> {
+    /// Creates a new, empty cache holding at most `capacity` entries, up to
+    /// 80% of which may be protected.
+    ///
+    /// # Panics
+    /// Panics if `capacity` is 0, as nothing could ever be cached.
+    pub fn new(capacity: usize) -> Self {
+        Self::with_protected_ratio(capacity, 0.8)
+    }
+
+    /// Creates a new, empty cache holding at most `capacity` entries, up to
+    /// `protected_ratio` of which may be protected.
+    ///
+    /// # Panics
+    /// Panics if `capacity` is 0, or if `protected_ratio` is outside of
+    /// `0.0..=1.0`.
+    pub fn with_protected_ratio(capacity: usize, protected_ratio: f64) -> Self {
+        assert!(capacity > 0, "capacity of an SlruCache must be positive");
+        assert!(
+            (0.0..=1.0).contains(&protected_ratio),
+            "protected ratio of an SlruCache must be between 0 and 1"
+        );
+        SlruCache {
+            probation: DoublyLinkedList::new(),
+            protected: DoublyLinkedList::new(),
+            map: HashMap::with_capacity(capacity),
+            capacity,
+            protected_capacity: (capacity as f64 * protected_ratio) as usize,
+            stats: CacheStats::default(),
+        }
+    }
+
+    /// Marks the entry of `key` as used, promoting it to `protected`.
+    fn touch(&mut self, key: &K) -> Option<&mut (K, V)> {
+        let slot = self.map.get_mut(key)?;
+        let handle = match slot {
+            Slot::Protected(handle) => {
+                self.protected.move_to_front(handle).unwrap();
+                return Some(self.protected.get_mut_by_handle(handle).unwrap());
+            }
+            Slot::Probation(handle) if self.protected_capacity == 0 => {
+                self.probation.move_to_front(handle).unwrap();
+                return Some(self.probation.get_mut_by_handle(handle).unwrap());
+            }
+            Slot::Probation(handle) => {
+                let entry = self.probation.remove_by_handle(handle).unwrap();
+                let handle = self.protected.push_front_with_handle(entry);
+                *slot = Slot::Protected(handle.clone());
+                handle
+            }
+        };
+        if self.protected.len() > self.protected_capacity {
+            // Demoted entries get another chance in `probation`
+            let entry = self.protected.pop_back().unwrap();
+            let key = entry.0.clone();
+            let demoted = self.probation.push_front_with_handle(entry);
+            self.map.insert(key, Slot::Probation(demoted));
+        }
+        Some(self.protected.get_mut_by_handle(&handle).unwrap())
+    }
+}
+
+impl<K: Hash + Eq + Clone, V> Cache<K, V> for SlruCache<K, V> {
+    fn get(&mut self, key: &K) -> Option<&V> {
+        let hit = self.contains(key);
+        self.stats.record_lookup(hit);
+        self.touch(key).map(|(_, val)| &*val)
+    }
+
+    fn peek(&self, key: &K) -> Option<&V> {
+        match self.map.get(key)? {
+            Slot::Probation(handle) => Some(&self.probation.get_by_handle(handle).unwrap().1),
+            Slot::Protected(handle) => Some(&self.protected.get_by_handle(handle).unwrap().1),
+        }
+    }
+
+    fn contains(&self, key: &K) -> bool {
+        self.map.contains_key(key)
+    }
+
+    fn put(&mut self, key: K, val: V) -> Option<V> {
+        if let Some(entry) = self.touch(&key) {
+            return Some(std::mem::replace(&mut entry.1, val));
+        }
+        if self.len() == self.capacity {
+            let segment = if self.probation.is_empty() {
+                &mut self.protected
+            } else {
+                &mut self.probation
+            };
+            let (evicted, _) = segment.pop_back().unwrap();
+            self.map.remove(&evicted);
+            self.stats.evictions += 1;
+        }
+        let handle = self.probation.push_front_with_handle((key.clone(), val));
+        self.map.insert(key, Slot::Probation(handle));
+        None
+    }
+
+    fn pop(&mut self, key: &K) -> Option<V> {
+        let (_, val) = match self.map.remove(key)? {
+            Slot::Probation(handle) => self.probation.remove_by_handle(&handle),
+            Slot::Protected(handle) => self.protected.remove_by_handle(&handle),
+        }
+        .unwrap();
+        Some(val)
+    }
+
+    fn clear(&mut self) {
+        self.map.clear();
+        self.probation.clear();
+        self.protected.clear();
+    }
+
+    fn len(&self) -> usize {
+        self.probation.len() + self.protected.len()
+    }
+
+    fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    fn stats(&self) -> CacheStats {
+        self.stats
+    }
+
+    fn reset_stats(&mut self) {
+        self.stats = CacheStats::default();
+    }
+}
+
+impl<K, V> fmt::Debug for SlruCache<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SlruCache")
+            .field("probation", &self.probation.len())
+            .field("protected", &self.protected.len())
+            .field("capacity", &self.capacity)
+            .field("stats", &self.stats)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn keys(list: &DoublyLinkedList<(i32, i32)>) -> Vec<i32> {
+        list.iter().map(|&(key, _)| key).collect()
+    }
+
+    #[test]
+    fn test_promotion_and_demotion() {
+        let mut cache = SlruCache::with_protected_ratio(4, 0.5);
+        (0..4).for_each(|key| {
+            cache.put(key, key);
+        });
+        assert_eq!(cache.get(&0), Some(&0));
+        assert_eq!(cache.get(&1), Some(&1));
+        assert_eq!(keys(&cache.protected), [1, 0]);
+
+        // A third protected entry demotes the least recently used one
+        assert_eq!(cache.put(2, 20), Some(2));
+        assert_eq!(keys(&cache.protected), [2, 1]);
+        assert_eq!(keys(&cache.probation), [0, 3]);
+
+        // Evictions come from `probation`
+        cache.put(4, 4);
+        cache.put(5, 5);
+        assert_eq!(keys(&cache.probation), [5, 4]);
+        assert_eq!(cache.peek(&2), Some(&20));
+        assert_eq!(cache.get(&3), None);
+        assert_eq!(
+            cache.stats(),
+            CacheStats {
+                hits: 2,
+                misses: 1,
+                evictions: 2
+            }
+        );
+    }
+
+    #[test]
+    fn test_pop_and_evict_from_protected() {
+        let mut cache = SlruCache::with_protected_ratio(2, 1.0);
+        cache.put(0, 0);
+        cache.put(1, 1);
+        cache.get(&0);
+        cache.get(&1);
+        assert!(cache.probation.is_empty());
+
+        // Nothing is on probation, so the LRU protected entry goes
+        cache.put(2, 2);
+        assert!(!cache.contains(&0));
+        assert_eq!(cache.pop(&1), Some(1));
+        assert_eq!(cache.pop(&2), Some(2));
+        assert!(cache.is_empty());
+
+        let mut cache = SlruCache::with_protected_ratio(2, 0.0);
+        cache.put(0, 0);
+        cache.put(1, 1);
+        cache.get(&0);
+        cache.put(2, 2);
+        assert!(cache.protected.is_empty());
+        assert_eq!(keys(&cache.probation), [2, 0]);
+        cache.clear();
+        assert!(cache.is_empty());
+    }
+}
@@ -0,0 +1,254 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::hash::Hash;
+
+use super::{Cache, CacheStats};
+use crate::{DoublyLinkedList, NodeHandle};
+
+/// Where the entry of a key lives.
+enum Slot<K, V> {
+    Recent(NodeHandle<(K, V)>),
+    Frequent(NodeHandle<(K, V)>),
+    Ghost(NodeHandle<K>),
+}
+
+/// A 2Q cache, which keeps one-off accesses from flushing out the entries
+/// that are used over and over.
+///
+/// New entries go into a FIFO `recent` list. Keys evicted from it are
+/// remembered, without their values, in a FIFO `ghosts` list, and only a key
+/// that is put again while remembered there makes it into the LRU `frequent`
+/// list. A scan over many keys therefore only churns `recent`.
+///
+/// ```
+/// use dll_rs::cache::{Cache, TwoQueueCache};
+///
+/// let mut cache = TwoQueueCache::new(4);
+/// cache.put(0, "hot");
+/// // Pushed out of `recent`, then put again from the ghosts
+/// (1..5).for_each(|key| { cache.put(key, "scan"); });
+/// cache.put(0, "hot");
+///
+/// // A long scan cannot evict it anymore
+/// (100..200).for_each(|key| { cache.put(key, "scan"); });
+/// assert_eq!(cache.get(&0), Some(&"hot"));
+/// ```
+pub struct TwoQueueCache<K, V> {
+    recent: DoublyLinkedList<(K, V)>,   // Newest first
+    frequent: DoublyLinkedList<(K, V)>, // Most recently used first
+    ghosts: DoublyLinkedList<K>,        // Newest first
+    map: HashMap<K, Slot<K, V>>,
+    capacity: usize,
+    recent_capacity: usize,
+    ghost_capacity: usize,
+    stats: CacheStats,
+}
+
+impl<K: Hash + Eq + Clone, V> TwoQueueCache<K, V> {
+    /// Creates a new, empty cache holding at most `capacity` entries.
+    ///
+    /// A quarter of the capacity is set aside for `recent`, and half as
+    /// many keys as the capacity are remembered as ghosts, the sizes
+    /// suggested by the paper introducing 2Q.
+    ///
+    /// # Panics
+    /// Panics if `capacity` is 0, as nothing could ever be cached.
+    pub fn new(capacity: usize) -> Self {
+        Self::with_ratios(capacity, 0.25, 0.5)
+    }
+
+    /// Creates a new, empty cache holding at most `capacity` entries, with
+    /// `recent` sized to `recent_ratio` of the capacity and room for
+    /// `ghost_ratio` times the capacity in `ghosts`.
+    ///
+    /// # Panics
+    /// Panics if `capacity` is 0, or if either ratio is outside of `0.0..=1.0`.
+    pub fn with_ratios(capacity: usize, recent_ratio: f64, ghost_ratio: f64) -> Self {
+        assert!(capacity > 0, "capacity of a TwoQueueCache must be positive");
+        assert!(
+            (0.0..=1.0).contains(&recent_ratio) && (0.0..=1.0).contains(&ghost_ratio),
+            "ratios of a TwoQueueCache must be between 0 and 1"
+        );
+        TwoQueueCache {
+            recent: DoublyLinkedList::new(),
+            frequent: DoublyLinkedList::new(),
+            ghosts: DoublyLinkedList::new(),
+            map: HashMap::new(),
+            capacity,
+            recent_capacity: (capacity as f64 * recent_ratio) as usize,
+            ghost_capacity: (capacity as f64 * ghost_ratio) as usize,
+            stats: CacheStats::default(),
+        }
+    }
+
+    /// Evicts an entry if the cache is full: from `recent` while it is over
+    /// its share, remembering the key as a ghost, and from `frequent`
+    /// otherwise.
+    fn make_room(&mut self) {
+        if self.len() < self.capacity {
+            return;
+        }
+        if self.recent.len() > self.recent_capacity || self.frequent.is_empty() {
+            let (key, _) = self.recent.pop_back().unwrap();
+            if self.ghost_capacity == 0 {
+                self.map.remove(&key);
+            } else {
+                if self.ghosts.len() == self.ghost_capacity {
+                    let forgotten = self.ghosts.pop_back().unwrap();
+                    self.map.remove(&forgotten);
+                }
+                let handle = self.ghosts.push_front_with_handle(key.clone());
+                self.map.insert(key, Slot::Ghost(handle));
+            }
+        } else {
+            let (key, _) = self.frequent.pop_back().unwrap();
+            self.map.remove(&key);
+        }
+        self.stats.evictions += 1;
+    }
+}
+
+impl<K: Hash + Eq + Clone, V> Cache<K, V> for TwoQueueCache<K, V> {
+    fn get(&mut self, key: &K) -> Option<&V> {
+        let val = match self.map.get(key) {
+            // Entries in `recent` keep their place in the FIFO
+            Some(Slot::Recent(handle)) => Some(&self.recent.get_by_handle(handle).unwrap().1),
+            Some(Slot::Frequent(handle)) => {
+                self.frequent.move_to_front(handle).unwrap();
+                Some(&self.frequent.get_by_handle(handle).unwrap().1)
+            }
+            Some(Slot::Ghost(_)) | None => None,
+        };
+        self.stats.record_lookup(val.is_some());
+        val
+    }
+
+    fn peek(&self, key: &K) -> Option<&V> {
+        match self.map.get(key)? {
+            Slot::Recent(handle) => Some(&self.recent.get_by_handle(handle).unwrap().1),
+            Slot::Frequent(handle) => Some(&self.frequent.get_by_handle(handle).unwrap().1),
+            Slot::Ghost(_) => None,
+        }
+    }
+
+    fn put(&mut self, key: K, val: V) -> Option<V> {
+        match self.map.get(&key) {
+            Some(Slot::Recent(handle)) => {
+                let entry = self.recent.get_mut_by_handle(handle).unwrap();
+                return Some(std::mem::replace(&mut entry.1, val));
+            }
+            Some(Slot::Frequent(handle)) => {
+                self.frequent.move_to_front(handle).unwrap();
+                let entry = self.frequent.get_mut_by_handle(handle).unwrap();
+                return Some(std::mem::replace(&mut entry.1, val));
+            }
+            Some(Slot::Ghost(handle)) => {
+                self.ghosts.remove_by_handle(handle).unwrap();
+                self.make_room();
+                let handle = self.frequent.push_front_with_handle((key.clone(), val));
+                self.map.insert(key, Slot::Frequent(handle));
+            }
+            None => {
+                self.make_room();
+                let handle = self.recent.push_front_with_handle((key.clone(), val));
+                self.map.insert(key, Slot::Recent(handle));
+            }
+        }
+        None
+    }
+
+    fn pop(&mut self, key: &K) -> Option<V> {
+        match self.map.remove(key)? {
+            Slot::Recent(handle) => Some(self.recent.remove_by_handle(&handle).unwrap().1),
+            Slot::Frequent(handle) => Some(self.frequent.remove_by_handle(&handle).unwrap().1),
+            Slot::Ghost(handle) => {
+                self.ghosts.remove_by_handle(&handle).unwrap();
+                None
+            }
+        }
+    }
+
+    fn clear(&mut self) {
+        self.map.clear();
+        self.recent.clear();
+        self.frequent.clear();
+        self.ghosts.clear();
+    }
+
+    fn len(&self) -> usize {
+        self.recent.len() + self.frequent.len()
+    }
+
+    fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    fn stats(&self) -> CacheStats {
+        self.stats
+    }
+
+    fn reset_stats(&mut self) {
+        self.stats = CacheStats::default();
+    }
+}
+
+impl<K, V> fmt::Debug for TwoQueueCache<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TwoQueueCache")
+            .field("recent", &self.recent.len())
+            .field("frequent", &self.frequent.len())
+            .field("ghosts", &self.ghosts.len())
+            .field("capacity", &self.capacity)
+            .field("stats", &self.stats)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ghost_hit_promotes_to_frequent() {
+        let mut cache = TwoQueueCache::with_ratios(4, 0.5, 0.5);
+        (0..4).for_each(|key| assert_eq!(cache.put(key, key * 10), None));
+        assert_eq!(cache.put(3, 33), Some(30));
+
+        // 0 is evicted to the ghosts and comes back into `frequent`
+        cache.put(4, 40);
+        assert_eq!(cache.peek(&0), None);
+        assert!(!cache.contains(&0));
+        cache.put(0, 0);
+        assert_eq!(cache.frequent.len(), 1);
+        assert_eq!(cache.len(), 4);
+
+        // Ghosts are forgotten oldest first, 1 is evicted as well
+        cache.put(5, 50);
+        cache.put(6, 60);
+        assert_eq!(cache.ghosts.iter().copied().collect::<Vec<_>>(), [3, 2]);
+        cache.put(1, 10);
+        assert_eq!(cache.frequent.len(), 1);
+        assert_eq!(cache.get(&0), Some(&0));
+        assert_eq!(cache.get(&1), Some(&10));
+        assert_eq!(cache.get(&2), None);
+        assert_eq!(cache.stats().evictions, 5);
+    }
+
+    #[test]
+    fn test_pop_and_clear() {
+        let mut cache = TwoQueueCache::new(4);
+        (0..5).for_each(|key| {
+            cache.put(key, key);
+        });
+        assert_eq!(cache.pop(&0), None); // A ghost
+        assert_eq!(cache.pop(&1), Some(1));
+        cache.put(0, 0);
+        assert_eq!(cache.recent.len(), 4);
+
+        cache.clear();
+        assert!(cache.is_empty());
+        assert!(cache.ghosts.is_empty());
+        cache.put(0, 0);
+        assert_eq!(cache.recent.len(), 1);
+    }
+}
@@ -0,0 +1,374 @@
+#[cfg(loom)]
+use loom::sync::{
+    Arc, Mutex, MutexGuard,
+    atomic::{AtomicUsize, Ordering},
+};
+use std::fmt;
+#[cfg(not(loom))]
+use std::sync::{
+    Arc, Mutex, MutexGuard,
+    atomic::{AtomicUsize, Ordering},
+};
+
+// Type aliases to make the code more readable
+type NodeRef<T> = Arc<Mutex<Node<T>>>;
+type Link<T> = Option<NodeRef<T>>;
+
+/// Internal Node structure for the list
+///
+/// Both links are strong: the list unlinks every node it removes, which
+/// breaks the cycles. `val` is `None` for the two sentinels and for nodes
+/// that have been popped.
+struct Node<T> {
+    val: Option<T>,
+    next: Link<T>,
+    prev: Link<T>,
+}
+
+impl<T> Node<T> {
+    fn new(val: Option<T>) -> NodeRef<T> {
+        Arc::new(Mutex::new(Node {
+            val,
+            next: None,
+            prev: None,
+        }))
+    }
+}
+
+/// Locks a node. A poisoned lock only means another thread panicked while
+/// holding it; links are always updated before anything that could panic.
+fn lock<T>(node: &NodeRef<T>) -> MutexGuard<'_, Node<T>> {
+    node.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+/// A doubly linked list that can be shared between threads.
+///
+/// Every node, including a sentinel at each end, has its own lock, so
+/// operations at the two ends of a list with more than two elements never
+/// contend with each other. Locks are always taken front to back along the
+/// list, which rules out deadlocks, and operations that have to find a node
+/// from the back re-validate the links once they hold the locks.
+///
+/// ```
+/// use std::sync::Arc;
+/// use std::thread;
+///
+/// use dll_rs::SyncDoublyLinkedList;
+///
+/// let list = Arc::new(SyncDoublyLinkedList::new());
+/// let producers: Vec<_> = (0..4)
+///     .map(|i| {
+///         let list = Arc::clone(&list);
+///         thread::spawn(move || list.push_back(i))
+///     })
+///     .collect();
+/// producers.into_iter().for_each(|t| t.join().unwrap());
+///
+/// assert_eq!(list.len(), 4);
+/// let mut popped: Vec<_> = std::iter::from_fn(|| list.pop_front()).collect();
+/// popped.sort();
+/// assert_eq!(popped, [0, 1, 2, 3]);
+/// ```
+pub struct SyncDoublyLinkedList<T> {
+    head: NodeRef<T>, // Sentinel, `head.next` is the first element
+    tail: NodeRef<T>, // Sentinel, `tail.prev` is the last element
+    len: AtomicUsize,
+}
+
+impl<T> SyncDoublyLinkedList<T> {
+    /// Creates a new, empty list.
+    pub fn new() -> Self {
+        let head = Node::new(None);
+        let tail = Node::new(None);
+        lock(&head).next = Some(Arc::clone(&tail));
+        lock(&tail).prev = Some(Arc::clone(&head));
+        SyncDoublyLinkedList {
+            head,
+            tail,
+            len: AtomicUsize::new(0),
+        }
+    }
+
+    /// Returns the number of elements in the list. With other threads
+    /// pushing or popping, this is only a snapshot.
+    pub fn len(&self) -> usize {
+        self.len.load(Ordering::Acquire)
+    }
+
+    /// Returns true if the list contains no elements; see [`Self::len`].
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Adds an element to the front of the list.
+    pub fn push_front(&self, val: T) {
+        let new_node = Node::new(Some(val));
+        let mut head = lock(&self.head);
+        let next = head.next.clone().expect("head sentinel is always linked");
+        let mut next_guard = lock(&next);
+        {
+            let mut node = lock(&new_node);
+            node.prev = Some(Arc::clone(&self.head));
+            node.next = Some(Arc::clone(&next));
+        }
+        next_guard.prev = Some(Arc::clone(&new_node));
+        head.next = Some(new_node);
+        self.len.fetch_add(1, Ordering::Release);
+    }
+
+    /// Adds an element to the back of the list.
+    pub fn push_back(&self, val: T) {
+        let new_node = Node::new(Some(val));
+        loop {
+            let prev = self.last();
+            let mut prev_guard = lock(&prev);
+            let mut tail = lock(&self.tail);
+            // `prev` may have been popped or had a node pushed after it
+            // while no locks were held
+            if !tail.prev.as_ref().is_some_and(|p| Arc::ptr_eq(p, &prev)) {
+                continue;
+            }
+            {
+                let mut node = lock(&new_node);
+                node.prev = Some(Arc::clone(&prev));
+                node.next = Some(Arc::clone(&self.tail));
+            }
+            tail.prev = Some(Arc::clone(&new_node));
+            prev_guard.next = Some(new_node);
+            self.len.fetch_add(1, Ordering::Release);
+            return;
+        }
+    }
+
+    /// Removes the first element and returns it, or `None` if the list is empty.
+    pub fn pop_front(&self) -> Option<T> {
+        let mut head = lock(&self.head);
+        let first = head.next.clone().expect("head sentinel is always linked");
+        if Arc::ptr_eq(&first, &self.tail) {
+            return None;
+        }
+        let mut first_guard = lock(&first);
+        let next = first_guard.next.take().expect("linked node has a next");
+        let mut next_guard = lock(&next);
+        first_guard.prev = None;
+        next_guard.prev = Some(Arc::clone(&self.head));
+        head.next = Some(next.clone());
+        self.len.fetch_sub(1, Ordering::Release);
+        first_guard.val.take()
+    }
+
+    /// Removes the last element and returns it, or `None` if the list is empty.
+    pub fn pop_back(&self) -> Option<T> {
+        loop {
+            let last = self.last();
+            if Arc::ptr_eq(&last, &self.head) {
+                return None;
+            }
+            let Some(prev) = lock(&last).prev.clone() else {
+                // Popped from the front in the meantime
+                continue;
+            };
+            let mut prev_guard = lock(&prev);
+            let mut last_guard = lock(&last);
+            let mut tail = lock(&self.tail);
+            let still_linked = tail.prev.as_ref().is_some_and(|p| Arc::ptr_eq(p, &last))
+                && last_guard
+                    .prev
+                    .as_ref()
+                    .is_some_and(|p| Arc::ptr_eq(p, &prev));
+            if !still_linked {
+                continue;
+            }
+            last_guard.prev = None;
+            last_guard.next = None;
+            tail.prev = Some(Arc::clone(&prev));
+            prev_guard.next = Some(Arc::clone(&self.tail));
+            self.len.fetch_sub(1, Ordering::Release);
+            return last_guard.val.take();
+        }
+    }
+
+    /// Returns the node in front of the tail sentinel, which is the head
+    /// sentinel if the list is empty. Only a snapshot, as the tail's lock
+    /// is released again.
+    fn last(&self) -> NodeRef<T> {
+        lock(&self.tail)
+            .prev
+            .clone()
+            .expect("tail sentinel is always linked")
+    }
+}
+
+impl<T> Default for SyncDoublyLinkedList<T> {
+    /// Creates an empty `SyncDoublyLinkedList<T>`.
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> fmt::Debug for SyncDoublyLinkedList<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SyncDoublyLinkedList")
+            .field("len", &self.len())
+            .finish_non_exhaustive()
+    }
+}
+
+impl<T> Drop for SyncDoublyLinkedList<T> {
+    fn drop(&mut self) {
+        // Pop all elements to unlink nodes iteratively, then break the cycle
+        // between the two sentinels
+        while self.pop_front().is_some() {}
+        lock(&self.head).next = None;
+        lock(&self.tail).prev = None;
+    }
+}
+
+#[cfg(all(test, not(loom)))]
+mod tests {
+    use super::*;
+    use std::thread;
+
+    #[test]
+    fn test_single_threaded_deque() {
+        let list = SyncDoublyLinkedList::new();
+        assert_eq!(list.pop_back(), None);
+        list.push_back(2);
+        list.push_front(1);
+        list.push_back(3);
+        assert_eq!(list.len(), 3);
+        assert_eq!(list.pop_back(), Some(3));
+        assert_eq!(list.pop_front(), Some(1));
+        assert_eq!(list.pop_back(), Some(2));
+        assert_eq!(list.pop_front(), None);
+        assert!(list.is_empty());
+    }
+
+    #[test]
+    fn test_drop_frees_nodes() {
+        let val = Arc::new(());
+        let list = SyncDoublyLinkedList::new();
+        for _ in 0..3 {
+            list.push_back(Arc::clone(&val));
+        }
+        drop(list);
+        assert_eq!(Arc::strong_count(&val), 1);
+    }
+
+    #[test]
+    fn test_concurrent_push_and_pop_at_both_ends() {
+        const PER_THREAD: usize = 2_000;
+        let list = Arc::new(SyncDoublyLinkedList::new());
+
+        let workers: Vec<_> = (0..4)
+            .map(|t| {
+                let list = Arc::clone(&list);
+                thread::spawn(move || {
+                    let mut popped = Vec::new();
+                    for i in 0..PER_THREAD {
+                        let val = t * PER_THREAD + i;
+                        match (t + i) % 4 {
+                            0 => list.push_front(val),
+                            1 => list.push_back(val),
+                            2 => popped.extend(list.pop_front()),
+                            _ => popped.extend(list.pop_back()),
+                        }
+                    }
+                    popped
+                })
+            })
+            .collect();
+        let mut seen: Vec<_> = workers
+            .into_iter()
+            .flat_map(|worker| worker.join().unwrap())
+            .collect();
+        seen.extend(std::iter::from_fn(|| list.pop_back()));
+
+        // Every pushed value comes out exactly once
+        seen.sort();
+        let pushed: Vec<_> = (0..4)
+            .flat_map(|t| {
+                (0..PER_THREAD)
+                    .filter(move |i| (t + i) % 4 < 2)
+                    .map(move |i| t * PER_THREAD + i)
+            })
+            .collect::<std::collections::BTreeSet<_>>()
+            .into_iter()
+            .collect();
+        assert_eq!(seen, pushed);
+        assert!(list.is_empty());
+    }
+}
+
+#[cfg(all(test, loom))]
+mod loom_tests {
+    use super::*;
+    use loom::thread;
+
+    #[test]
+    fn loom_push_back_and_pop_front() {
+        loom::model(|| {
+            let list = Arc::new(SyncDoublyLinkedList::new());
+            let producer = {
+                let list = Arc::clone(&list);
+                thread::spawn(move || {
+                    list.push_back(1);
+                    list.push_back(2);
+                })
+            };
+            let popped = list.pop_front();
+            producer.join().unwrap();
+
+            // FIFO order holds even when racing with the producer
+            let rest: Vec<_> = std::iter::from_fn(|| list.pop_front()).collect();
+            match popped {
+                None => assert_eq!(rest, [1, 2]),
+                Some(1) => assert_eq!(rest, [2]),
+                other => panic!("popped {other:?} first"),
+            }
+        });
+    }
+
+    #[test]
+    fn loom_pop_both_ends_of_single_element() {
+        loom::model(|| {
+            let list = Arc::new(SyncDoublyLinkedList::new());
+            list.push_back(1);
+            let back = {
+                let list = Arc::clone(&list);
+                thread::spawn(move || list.pop_back())
+            };
+            let front = list.pop_front();
+            let back = back.join().unwrap();
+
+            // Exactly one of the two gets the element
+            assert_eq!(front.or(back), Some(1));
+            assert!(front.is_none() || back.is_none());
+            assert!(list.is_empty());
+        });
+    }
+
+    #[test]
+    fn loom_push_both_ends_and_pop_back() {
+        loom::model(|| {
+            let list = Arc::new(SyncDoublyLinkedList::new());
+            list.push_back(0);
+            let front = {
+                let list = Arc::clone(&list);
+                thread::spawn(move || list.push_front(1))
+            };
+            let back = {
+                let list = Arc::clone(&list);
+                thread::spawn(move || list.pop_back())
+            };
+            list.push_back(2);
+            front.join().unwrap();
+            let popped = back.join().unwrap();
+
+            let mut all: Vec<_> = std::iter::from_fn(|| list.pop_front()).collect();
+            all.extend(popped);
+            all.sort();
+            assert_eq!(all, [0, 1, 2]);
+        });
+    }
+}
@@ -1,47 +1,122 @@
-use std::cell::RefCell;
-use std::rc::{Rc, Weak};
+use std::cell::Cell;
+use std::cmp::Ordering as CmpOrdering;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::iter::FusedIterator;
+use std::marker::PhantomData;
+use std::ptr::NonNull;
+use std::rc::Rc;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+mod cursor;
+mod filter;
+mod handle;
+mod index;
+mod ops;
+#[cfg(feature = "serde")]
+mod serialize;
+mod sort;
+
+pub use cursor::{Cursor, CursorMut};
+pub use filter::{Drain, ExtractIf};
+pub use handle::{NodeHandle, StaleHandleError};
+pub use index::IndexOutOfBoundsError;
 
 // Type aliases to make the code more readable
-type Link<T> = Option<Rc<RefCell<Node<T>>>>;
-type WeakLink<T> = Option<Weak<RefCell<Node<T>>>>;
+type Link<T> = Option<NonNull<Node<T>>>;
+// A detached run of nodes as (first, last, len)
+type Chain<T> = (NonNull<Node<T>>, NonNull<Node<T>>, usize);
+// Where a node publishes its address to the `NodeHandle`s pointing at it
+type HandleSlot<T> = Rc<Cell<Link<T>>>;
 
 /// Internal Node structure for the list
-#[derive(Debug)]
+///
+/// Every node is a leaked `Box` owned by the list it is linked into, and is
+/// freed again by `Node::into_val` once unlinked. `next` and `prev` are plain
+/// pointers, so there are no reference counts or borrow flags to maintain.
 struct Node<T> {
     val: T,
     next: Link<T>,
-    prev: WeakLink<T>, // Use Weak to prevent reference cycles
+    prev: Link<T>,
+    handle: Option<HandleSlot<T>>, // Only set for nodes pushed `*_with_handle`
 }
 
 /// The Doubly Linked List
-#[derive(Debug)]
+///
+/// # Invariants
+/// The unsafe code in this module relies on the following, which every
+/// method upholds:
+/// - `head` and `tail` are both `None` or both `Some`, and `len` is the
+///   number of nodes reachable from `head` through `next`.
+/// - for every linked node `n`, `n.next.prev == n` and `n.prev.next == n`,
+///   `head.prev` and `tail.next` are `None`.
+/// - every node is owned by exactly one list and only accessed through it,
+///   so `&self` and `&mut self` borrows cover all of its nodes.
 pub struct DoublyLinkedList<T> {
     head: Link<T>,
     tail: Link<T>,
     len: usize,
+    id: u64, // Identifies the list to the `NodeHandle`s it hands out
+    _marker: PhantomData<Box<Node<T>>>, // The list owns its nodes
+}
+
+/// Returns a list id that has never been used before.
+fn next_list_id() -> u64 {
+    static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+    NEXT_ID.fetch_add(1, Ordering::Relaxed)
 }
 
 impl<T> Node<T> {
-    /// Creates a new node wrapped in Rc and RefCell
-    fn new(val: T) -> Rc<RefCell<Self>> {
-        Rc::new(RefCell::new(Node {
+    /// Allocates a new, unlinked node.
+    fn new(val: T) -> NonNull<Self> {
+        NonNull::from(Box::leak(Box::new(Node {
             val,
             next: None,
             prev: None,
-        }))
+            handle: None,
+        })))
+    }
+
+    /// Frees a node and moves its value out, marking its handles as stale.
+    ///
+    /// # Safety
+    /// `node` must come from `Node::new`, must not be linked into any list
+    /// and must not be used again afterwards.
+    unsafe fn into_val(node: NonNull<Self>) -> T {
+        let node = unsafe { Box::from_raw(node.as_ptr()) };
+        if let Some(slot) = &node.handle {
+            slot.set(None);
+        }
+        node.val
     }
 }
 
 impl<T> DoublyLinkedList<T> {
     /// Creates a new, empty doubly linked list.
     /// ```
+    /// use dll_rs::DoublyLinkedList;
+    ///
     /// let list: DoublyLinkedList<i32> = DoublyLinkedList::new();
+    /// assert!(list.is_empty());
     /// ```
     pub fn new() -> Self {
         DoublyLinkedList {
             head: None,
             tail: None,
             len: 0,
+            id: next_list_id(),
+            _marker: PhantomData,
+        }
+    }
+
+    /// Wraps a detached chain of nodes into a new list.
+    fn from_chain((head, tail, len): Chain<T>) -> Self {
+        DoublyLinkedList {
+            head: Some(head),
+            tail: Some(tail),
+            len,
+            id: next_list_id(),
+            _marker: PhantomData,
         }
     }
 
@@ -55,100 +130,536 @@ impl<T> DoublyLinkedList<T> {
         self.len == 0
     }
 
+    /// Returns a reference to the first element, or `None` if the list is empty.
+    /// ```
+    /// use dll_rs::DoublyLinkedList;
+    ///
+    /// let mut list = DoublyLinkedList::from([1, 2]);
+    /// assert_eq!(list.front(), Some(&1));
+    /// if let Some(front) = list.front_mut() {
+    ///     *front = 10;
+    /// }
+    /// assert_eq!(list.pop_front(), Some(10));
+    /// ```
+    pub fn front(&self) -> Option<&T> {
+        // SAFETY: the head is owned by the list, which is borrowed for the
+        // returned lifetime.
+        self.head.map(|node| unsafe { &(*node.as_ptr()).val })
+    }
+
+    /// Returns a reference to the last element, or `None` if the list is empty.
+    pub fn back(&self) -> Option<&T> {
+        // SAFETY: see `front`.
+        self.tail.map(|node| unsafe { &(*node.as_ptr()).val })
+    }
+
+    /// Returns a mutable reference to the first element, or `None` if the
+    /// list is empty.
+    pub fn front_mut(&mut self) -> Option<&mut T> {
+        // SAFETY: the list is exclusively borrowed for the returned lifetime.
+        self.head.map(|node| unsafe { &mut (*node.as_ptr()).val })
+    }
+
+    /// Returns a mutable reference to the last element, or `None` if the
+    /// list is empty.
+    pub fn back_mut(&mut self) -> Option<&mut T> {
+        // SAFETY: see `front_mut`.
+        self.tail.map(|node| unsafe { &mut (*node.as_ptr()).val })
+    }
+
     /// Adds an element to the front of the list.
+    /// ```
+    /// use dll_rs::DoublyLinkedList;
+    ///
+    /// let mut list = DoublyLinkedList::new();
+    /// list.push_front(2);
+    /// list.push_front(1);
+    /// assert_eq!(list.pop_front(), Some(1));
+    /// ```
     pub fn push_front(&mut self, val: T) {
         let new_head = Node::new(val);
+        // SAFETY: the new node is unlinked
+        unsafe { self.link_between(None, self.head, new_head) };
+    }
 
-        match self.head.take() {
-            // List was not empty
-            Some(old_head) => {
-                old_head.borrow_mut().prev = Some(Rc::downgrade(&new_head));
-                new_head.borrow_mut().next = Some(old_head);
-                self.head = Some(new_head);
+    /// Adds an element to the back of the list.
+    /// ```
+    /// use dll_rs::DoublyLinkedList;
+    ///
+    /// let mut list = DoublyLinkedList::new();
+    /// list.push_back(1);
+    /// list.push_back(2);
+    /// assert_eq!(list.pop_back(), Some(2));
+    /// ```
+    pub fn push_back(&mut self, val: T) {
+        let new_tail = Node::new(val);
+        // SAFETY: the new node is unlinked
+        unsafe { self.link_between(self.tail, None, new_tail) };
+    }
+
+    /// Removes the first element and returns it, or `None` if the list is empty.
+    pub fn pop_front(&mut self) -> Option<T> {
+        self.head.map(|old_head| {
+            // SAFETY: the head belongs to this list, and is freed right after
+            // being unlinked
+            unsafe {
+                self.unlink(old_head);
+                Node::into_val(old_head)
             }
-            // List was empty
-            None => {
-                self.tail = Some(Rc::clone(&new_head));
-                self.head = Some(new_head);
+        })
+    }
+
+    /// Removes the last element and returns it, or `None` if the list is empty.
+    pub fn pop_back(&mut self) -> Option<T> {
+        self.tail.map(|old_tail| {
+            // SAFETY: see `pop_front`.
+            unsafe {
+                self.unlink(old_tail);
+                Node::into_val(old_tail)
             }
+        })
+    }
+
+    /// Returns an iterator over references to the elements, front to back.
+    /// ```
+    /// use dll_rs::DoublyLinkedList;
+    ///
+    /// let mut list = DoublyLinkedList::new();
+    /// list.push_back(1);
+    /// list.push_back(2);
+    /// list.push_back(3);
+    ///
+    /// let forward: Vec<_> = list.iter().copied().collect();
+    /// let backward: Vec<_> = list.iter().rev().copied().collect();
+    /// assert_eq!(forward, [1, 2, 3]);
+    /// assert_eq!(backward, [3, 2, 1]);
+    /// ```
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            head: self.head,
+            tail: self.tail,
+            len: self.len,
+            _marker: PhantomData,
         }
-        self.len += 1;
     }
 
-    /// Adds an element to the back of the list.
-    pub fn push_back(&mut self, val: T) {
-        let new_tail = Node::new(val);
+    /// Returns an iterator over mutable references to the elements, front to back.
+    /// ```
+    /// use dll_rs::DoublyLinkedList;
+    ///
+    /// let mut list = DoublyLinkedList::new();
+    /// list.push_back(1);
+    /// list.push_back(2);
+    ///
+    /// for val in list.iter_mut() {
+    ///     *val *= 10;
+    /// }
+    /// assert_eq!(list.pop_front(), Some(10));
+    /// assert_eq!(list.pop_front(), Some(20));
+    /// ```
+    pub fn iter_mut(&mut self) -> IterMut<'_, T> {
+        IterMut {
+            head: self.head,
+            tail: self.tail,
+            len: self.len,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Returns a cursor pointing at the first element, or at the "ghost"
+    /// non-element if the list is empty.
+    pub fn cursor_front(&self) -> Cursor<'_, T> {
+        Cursor::new(self, self.head, 0)
+    }
 
-        match self.tail.take() {
-            // List was not empty
-            Some(old_tail) => {
-                old_tail.borrow_mut().next = Some(Rc::clone(&new_tail));
-                new_tail.borrow_mut().prev = Some(Rc::downgrade(&old_tail));
-                self.tail = Some(new_tail);
+    /// Returns a cursor pointing at the last element, or at the "ghost"
+    /// non-element if the list is empty.
+    pub fn cursor_back(&self) -> Cursor<'_, T> {
+        let index = self.len.saturating_sub(1);
+        Cursor::new(self, self.tail, index)
+    }
+
+    /// Returns a mutable cursor pointing at the first element, or at the
+    /// "ghost" non-element if the list is empty.
+    /// ```
+    /// use dll_rs::DoublyLinkedList;
+    ///
+    /// let mut list = DoublyLinkedList::from([1, 3]);
+    /// let mut cursor = list.cursor_front_mut();
+    /// cursor.insert_after(2);
+    /// cursor.move_next();
+    /// assert_eq!(cursor.current(), Some(&mut 2));
+    /// assert_eq!(list.iter().copied().collect::<Vec<_>>(), [1, 2, 3]);
+    /// ```
+    pub fn cursor_front_mut(&mut self) -> CursorMut<'_, T> {
+        let current = self.head;
+        CursorMut::new(self, current, 0)
+    }
+
+    /// Returns a mutable cursor pointing at the last element, or at the
+    /// "ghost" non-element if the list is empty.
+    pub fn cursor_back_mut(&mut self) -> CursorMut<'_, T> {
+        let current = self.tail;
+        let index = self.len.saturating_sub(1);
+        CursorMut::new(self, current, index)
+    }
+
+    /// Links `node` between `prev` and `next`. `None` stands for the end of
+    /// the list on that side.
+    ///
+    /// # Safety
+    /// `prev` and `next` must be adjacent nodes of this list and `node` must
+    /// be unlinked; see [`DoublyLinkedList::splice_between`].
+    unsafe fn link_between(&mut self, prev: Link<T>, next: Link<T>, node: NonNull<Node<T>>) {
+        unsafe { self.splice_between(prev, next, (node, node, 1)) };
+    }
+
+    /// Links the detached chain `first..=last` of `len` nodes between `prev`
+    /// and `next`. `None` stands for the end of the list on that side.
+    ///
+    /// # Safety
+    /// `prev` and `next` must be adjacent nodes of this list (or its ends),
+    /// and the chain must be properly linked internally, not part of any
+    /// list, and exactly `len` nodes long.
+    unsafe fn splice_between(&mut self, prev: Link<T>, next: Link<T>, chain: Chain<T>) {
+        let (first, last, len) = chain;
+        unsafe {
+            (*first.as_ptr()).prev = prev;
+            (*last.as_ptr()).next = next;
+            match next {
+                Some(next) => (*next.as_ptr()).prev = Some(last),
+                None => self.tail = Some(last),
             }
-            // List was empty
-            None => {
-                self.head = Some(Rc::clone(&new_tail));
-                self.tail = Some(new_tail);
+            match prev {
+                Some(prev) => (*prev.as_ptr()).next = Some(first),
+                None => self.head = Some(first),
             }
         }
-        self.len += 1;
+        self.len += len;
     }
 
-    /// Removes the first element and returns it, or `None` if the list is empty.
-    pub fn pop_front(&mut self) -> Option<T> {
-        self.head.take().map(|old_head| {
-            match old_head.borrow_mut().next.take() {
-                // List has more than one element
-                Some(new_head) => {
-                    new_head.borrow_mut().prev.take();
-                    self.head = Some(new_head);
-                }
-                // List is now empty
-                None => {
-                    self.tail.take();
-                }
+    /// Takes every node out of the list, leaving it empty, and returns the
+    /// detached chain as `(head, tail, len)`.
+    fn take_chain(&mut self) -> Option<Chain<T>> {
+        let head = self.head.take()?;
+        let tail = self.tail.take()?;
+        self.invalidate_handles();
+        Some((head, tail, std::mem::take(&mut self.len)))
+    }
+
+    /// Gives the list a fresh id so every `NodeHandle` handed out so far is
+    /// reported as stale. Needed whenever live nodes move to another list.
+    fn invalidate_handles(&mut self) {
+        self.id = next_list_id();
+    }
+
+    /// Unlinks `node` from this list and joins its neighbours, leaving the
+    /// node detached and owned by the caller.
+    ///
+    /// # Safety
+    /// `node` must be linked into this list.
+    unsafe fn unlink(&mut self, node: NonNull<Node<T>>) {
+        unsafe {
+            let prev = (*node.as_ptr()).prev.take();
+            let next = (*node.as_ptr()).next.take();
+            match next {
+                Some(next) => (*next.as_ptr()).prev = prev,
+                None => self.tail = prev,
+            }
+            match prev {
+                Some(prev) => (*prev.as_ptr()).next = next,
+                None => self.head = next,
             }
+        }
+        self.len -= 1;
+    }
+}
+
+impl<T> Default for DoublyLinkedList<T> {
+    /// Creates an empty `DoublyLinkedList<T>`.
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Immutable iterator over a `DoublyLinkedList`, created by [`DoublyLinkedList::iter`].
+pub struct Iter<'a, T> {
+    head: Link<T>,
+    tail: Link<T>,
+    len: usize,
+    _marker: PhantomData<&'a Node<T>>,
+}
+
+impl<T> Clone for Iter<'_, T> {
+    fn clone(&self) -> Self {
+        Iter { ..*self }
+    }
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.len == 0 {
+            return None;
+        }
+        self.head.map(|node| {
+            // SAFETY: the list, and with it every node, is borrowed for 'a
+            let node = unsafe { &*node.as_ptr() };
             self.len -= 1;
-            // Unwraps the value from Rc<RefCell<Node<T>>>
-            Rc::try_unwrap(old_head).ok().unwrap().into_inner().val
+            self.head = node.next;
+            &node.val
         })
     }
 
-    /// Removes the last element and returns it, or `None` if the list is empty.
-    pub fn pop_back(&mut self) -> Option<T> {
-        self.tail.take().map(|old_tail| {
-            match old_tail
-                .borrow_mut()
-                .prev
-                .take()
-                .and_then(|weak_prev| weak_prev.upgrade())
-            {
-                // List has more than one element
-                Some(new_tail) => {
-                    new_tail.borrow_mut().next.take();
-                    self.tail = Some(new_tail);
-                }
-                // List is now empty
-                None => {
-                    self.head.take();
-                }
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<T> DoubleEndedIterator for Iter<'_, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.len == 0 {
+            return None;
+        }
+        self.tail.map(|node| {
+            // SAFETY: see `next`.
+            let node = unsafe { &*node.as_ptr() };
+            self.len -= 1;
+            self.tail = node.prev;
+            &node.val
+        })
+    }
+}
+
+impl<T> ExactSizeIterator for Iter<'_, T> {}
+
+impl<T> FusedIterator for Iter<'_, T> {}
+
+/// Mutable iterator over a `DoublyLinkedList`, created by [`DoublyLinkedList::iter_mut`].
+///
+/// The list is exclusively borrowed for `'a` and the remaining `len` keeps
+/// the two ends from ever yielding the same node twice.
+pub struct IterMut<'a, T> {
+    head: Link<T>,
+    tail: Link<T>,
+    len: usize,
+    _marker: PhantomData<&'a mut Node<T>>,
+}
+
+impl<'a, T> Iterator for IterMut<'a, T> {
+    type Item = &'a mut T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.len == 0 {
+            return None;
+        }
+        self.head.map(|node| {
+            let node = node.as_ptr();
+            self.len -= 1;
+            // SAFETY: the list is exclusively borrowed for 'a and every node is
+            // yielded at most once, so the returned reference is unique. Only
+            // the link is read, never through a reference to the whole node.
+            unsafe {
+                self.head = (*node).next;
+                &mut (*node).val
             }
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<T> DoubleEndedIterator for IterMut<'_, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.len == 0 {
+            return None;
+        }
+        self.tail.map(|node| {
+            let node = node.as_ptr();
             self.len -= 1;
-            // Unwraps the value from Rc<RefCell<Node<T>>>
-            Rc::try_unwrap(old_tail).ok().unwrap().into_inner().val
+            // SAFETY: see `next`.
+            unsafe {
+                self.tail = (*node).prev;
+                &mut (*node).val
+            }
         })
     }
 }
 
+impl<T> ExactSizeIterator for IterMut<'_, T> {}
+
+impl<T> FusedIterator for IterMut<'_, T> {}
+
+impl<'a, T> IntoIterator for &'a DoublyLinkedList<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, T> IntoIterator for &'a mut DoublyLinkedList<T> {
+    type Item = &'a mut T;
+    type IntoIter = IterMut<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+/// Owning iterator over a `DoublyLinkedList`, created by its `IntoIterator` impl.
+pub struct IntoIter<T> {
+    list: DoublyLinkedList<T>,
+}
+
+impl<T> Iterator for IntoIter<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.list.pop_front()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.list.len, Some(self.list.len))
+    }
+}
+
+impl<T> DoubleEndedIterator for IntoIter<T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.list.pop_back()
+    }
+}
+
+impl<T> ExactSizeIterator for IntoIter<T> {}
+
+impl<T> FusedIterator for IntoIter<T> {}
+
+impl<T> IntoIterator for DoublyLinkedList<T> {
+    type Item = T;
+    type IntoIter = IntoIter<T>;
+
+    /// Consumes the list into an iterator yielding elements by value.
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter { list: self }
+    }
+}
+
+impl<T> FromIterator<T> for DoublyLinkedList<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut list = DoublyLinkedList::new();
+        list.extend(iter);
+        list
+    }
+}
+
+impl<T> Extend<T> for DoublyLinkedList<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        iter.into_iter().for_each(|val| self.push_back(val));
+    }
+}
+
+impl<'a, T: 'a + Copy> Extend<&'a T> for DoublyLinkedList<T> {
+    fn extend<I: IntoIterator<Item = &'a T>>(&mut self, iter: I) {
+        self.extend(iter.into_iter().copied());
+    }
+}
+
+impl<T, const N: usize> From<[T; N]> for DoublyLinkedList<T> {
+    /// Converts a `[T; N]` into a `DoublyLinkedList<T>`, keeping the order.
+    /// ```
+    /// use dll_rs::DoublyLinkedList;
+    ///
+    /// let list = DoublyLinkedList::from([1, 2, 3]);
+    /// assert_eq!(list.into_iter().rev().collect::<Vec<_>>(), [3, 2, 1]);
+    /// ```
+    fn from(arr: [T; N]) -> Self {
+        Self::from_iter(arr)
+    }
+}
+
+impl<T> From<Vec<T>> for DoublyLinkedList<T> {
+    /// Converts a `Vec<T>` into a `DoublyLinkedList<T>`, keeping the order.
+    fn from(vec: Vec<T>) -> Self {
+        Self::from_iter(vec)
+    }
+}
+
+impl<T: Clone> Clone for DoublyLinkedList<T> {
+    /// Returns a deep copy of the list; the copy shares no nodes with `self`.
+    fn clone(&self) -> Self {
+        self.iter().cloned().collect()
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for DoublyLinkedList<T> {
+    /// Formats the list as its elements, front to back, e.g. `[1, 2, 3]`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self).finish()
+    }
+}
+
+impl<T: PartialEq> PartialEq for DoublyLinkedList<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len == other.len && self.iter().eq(other)
+    }
+}
+
+impl<T: Eq> Eq for DoublyLinkedList<T> {}
+
+impl<T: PartialOrd> PartialOrd for DoublyLinkedList<T> {
+    /// Compares the lists lexicographically.
+    fn partial_cmp(&self, other: &Self) -> Option<CmpOrdering> {
+        self.iter().partial_cmp(other)
+    }
+}
+
+impl<T: Ord> Ord for DoublyLinkedList<T> {
+    /// Compares the lists lexicographically.
+    fn cmp(&self, other: &Self) -> CmpOrdering {
+        self.iter().cmp(other)
+    }
+}
+
+impl<T: Hash> Hash for DoublyLinkedList<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        // Prefix the length so that e.g. nested lists hash unambiguously
+        state.write_usize(self.len);
+        for val in self {
+            val.hash(state);
+        }
+    }
+}
+
 // Implement Drop to prevent stack overflow on long lists
 impl<T> Drop for DoublyLinkedList<T> {
     fn drop(&mut self) {
-        // Pop all elements to ensure nodes are deallocated iteratively
-        while self.pop_front().is_some() {}
+        self.clear();
     }
 }
 
+/// Collects the elements of a list front to back, checking that walking it
+/// back to front agrees and that its length is right. Shared by the tests of
+/// every linked list in the crate.
+#[cfg(test)]
+pub(crate) fn collect<'a, L, T>(list: &'a L) -> Vec<T>
+where
+    &'a L: IntoIterator<Item = &'a T, IntoIter: DoubleEndedIterator + ExactSizeIterator>,
+    T: Copy + PartialEq + fmt::Debug + 'a,
+{
+    let iter = list.into_iter();
+    let len = iter.len();
+    let forward: Vec<_> = iter.copied().collect();
+    let mut backward: Vec<_> = list.into_iter().rev().copied().collect();
+    backward.reverse();
+    assert_eq!(forward, backward, "next and prev links disagree");
+    assert_eq!(forward.len(), len);
+    forward
+}
+
 // --- Tests ---
 #[cfg(test)]
 mod tests {
@@ -205,4 +716,132 @@ mod tests {
         assert_eq!(list.pop_front(), Some(2)); // list: []
         assert!(list.is_empty());
     }
+
+    #[test]
+    fn test_iter_both_directions() {
+        let mut list = DoublyLinkedList::new();
+        list.push_back(2);
+        list.push_front(1);
+        list.push_back(3);
+
+        let mut iter = list.iter();
+        assert_eq!(iter.len(), 3);
+        assert_eq!(iter.next(), Some(&1));
+        assert_eq!(iter.next_back(), Some(&3));
+        assert_eq!(iter.len(), 1);
+        assert_eq!(iter.next_back(), Some(&2));
+        assert_eq!(iter.next(), None);
+        assert_eq!(iter.next_back(), None);
+
+        // The list is untouched by iteration
+        assert_eq!(list.len(), 3);
+        assert_eq!(list.iter().rev().collect::<Vec<_>>(), [&3, &2, &1]);
+    }
+
+    #[test]
+    fn test_iter_mut() {
+        let mut list = DoublyLinkedList::new();
+        list.push_back(1);
+        list.push_back(2);
+        list.push_back(3);
+
+        let mut iter = list.iter_mut();
+        *iter.next().unwrap() += 10;
+        *iter.next_back().unwrap() += 30;
+        *iter.next().unwrap() += 20;
+        assert!(iter.next().is_none());
+
+        for val in &mut list {
+            *val *= 2;
+        }
+        assert_eq!(list.pop_front(), Some(22));
+        assert_eq!(list.pop_front(), Some(44));
+        assert_eq!(list.pop_front(), Some(66));
+    }
+
+    #[test]
+    fn test_iter_empty() {
+        let mut list: DoublyLinkedList<i32> = DoublyLinkedList::new();
+        assert_eq!(list.iter().next(), None);
+        assert_eq!(list.iter_mut().next_back(), None);
+    }
+
+    #[test]
+    fn test_into_iter() {
+        let list = DoublyLinkedList::from([1, 2, 3, 4]);
+
+        let mut iter = list.into_iter();
+        assert_eq!(iter.len(), 4);
+        assert_eq!(iter.next(), Some(1));
+        assert_eq!(iter.next_back(), Some(4));
+        assert_eq!(iter.collect::<Vec<_>>(), [2, 3]);
+    }
+
+    #[test]
+    fn test_from_iter_and_extend() {
+        let mut list: DoublyLinkedList<_> = (1..=3).collect();
+        list.extend(vec![4, 5]);
+        list.extend(&[6, 7]);
+        assert_eq!(list.len(), 7);
+        assert_eq!(
+            list.iter().copied().collect::<Vec<_>>(),
+            [1, 2, 3, 4, 5, 6, 7]
+        );
+
+        let list = DoublyLinkedList::from(vec!["a", "b"]);
+        assert_eq!(list.into_iter().collect::<Vec<_>>(), ["a", "b"]);
+    }
+
+    #[test]
+    fn test_peek_ends() {
+        let mut list = DoublyLinkedList::new();
+        assert_eq!(list.front(), None);
+        assert_eq!(list.back_mut(), None);
+
+        list.push_back(1);
+        assert_eq!(list.front(), list.back());
+        list.push_back(2);
+        *list.front_mut().unwrap() += 10;
+        *list.back_mut().unwrap() += 20;
+        assert_eq!((list.front(), list.back()), (Some(&11), Some(&22)));
+        assert_eq!(list.len(), 2);
+    }
+
+    #[test]
+    fn test_clone_is_deep() {
+        let mut list = DoublyLinkedList::from([1, 2, 3]);
+        let copy = list.clone();
+        *list.front_mut().unwrap() = 10;
+        assert_eq!(copy.front(), Some(&1));
+        assert_eq!(copy.iter().rev().copied().collect::<Vec<_>>(), [3, 2, 1]);
+        assert_ne!(list, copy);
+    }
+
+    #[test]
+    fn test_eq_and_ord() {
+        let list = DoublyLinkedList::from([1, 2, 3]);
+        assert_eq!(list, DoublyLinkedList::from([1, 2, 3]));
+        assert_ne!(list, DoublyLinkedList::from([1, 2]));
+        assert!(list > DoublyLinkedList::from([1, 2]));
+        assert!(list < DoublyLinkedList::from([1, 3]));
+        assert_eq!(DoublyLinkedList::<i32>::default(), DoublyLinkedList::new());
+    }
+
+    #[test]
+    fn test_hash_as_map_key() {
+        use std::collections::HashMap;
+
+        let mut map = HashMap::new();
+        map.insert(DoublyLinkedList::from(["a", "b"]), 1);
+        map.insert(DoublyLinkedList::from(["a"]), 2);
+        assert_eq!(map.get(&DoublyLinkedList::from(["a", "b"])), Some(&1));
+        assert_eq!(map.get(&DoublyLinkedList::from(["b"])), None);
+    }
+
+    #[test]
+    fn test_debug_as_list() {
+        let list = DoublyLinkedList::from([1, 2, 3]);
+        assert_eq!(format!("{list:?}"), "[1, 2, 3]");
+        assert_eq!(format!("{:?}", DoublyLinkedList::<u8>::new()), "[]");
+    }
 }
@@ -0,0 +1,449 @@
+use std::mem;
+
+use super::{DoublyLinkedList, Link, Node, NodeHandle};
+
+/// A cursor over a `DoublyLinkedList`.
+///
+/// A cursor points either at an element or at the "ghost" non-element that
+/// sits between the tail and the head, so moving past either end wraps
+/// around through the ghost. Created by [`DoublyLinkedList::cursor_front`]
+/// and [`DoublyLinkedList::cursor_back`].
+pub struct Cursor<'a, T> {
+    current: Link<T>,
+    index: usize,
+    list: &'a DoublyLinkedList<T>,
+}
+
+impl<T> Clone for Cursor<'_, T> {
+    fn clone(&self) -> Self {
+        Cursor { ..*self }
+    }
+}
+
+impl<'a, T> Cursor<'a, T> {
+    pub(super) fn new(list: &'a DoublyLinkedList<T>, current: Link<T>, index: usize) -> Self {
+        Cursor {
+            current,
+            index,
+            list,
+        }
+    }
+
+    /// Returns the node behind `link`, which must belong to `self.list`.
+    fn node(&self, link: Link<T>) -> Option<&'a Node<T>> {
+        // SAFETY: the list, and with it every node, is borrowed for 'a
+        link.map(|node| unsafe { &*node.as_ptr() })
+    }
+
+    /// Returns the index of the current element, or `None` at the ghost.
+    pub fn index(&self) -> Option<usize> {
+        self.current.map(|_| self.index)
+    }
+
+    /// Moves to the next element; from the tail this moves to the ghost and
+    /// from the ghost to the head.
+    pub fn move_next(&mut self) {
+        match self.node(self.current) {
+            None => {
+                self.current = self.list.head;
+                self.index = 0;
+            }
+            Some(node) => {
+                self.current = node.next;
+                self.index += 1;
+            }
+        }
+    }
+
+    /// Moves to the previous element; from the head this moves to the ghost
+    /// and from the ghost to the tail.
+    pub fn move_prev(&mut self) {
+        match self.node(self.current) {
+            None => {
+                self.current = self.list.tail;
+                self.index = self.list.len.saturating_sub(1);
+            }
+            Some(node) => {
+                self.current = node.prev;
+                match self.current {
+                    Some(_) => self.index -= 1,
+                    None => self.index = self.list.len,
+                }
+            }
+        }
+    }
+
+    /// Returns the current element, or `None` at the ghost.
+    pub fn current(&self) -> Option<&'a T> {
+        self.node(self.current).map(|node| &node.val)
+    }
+
+    /// Returns the next element without moving; at the ghost this is the head.
+    pub fn peek_next(&self) -> Option<&'a T> {
+        let next = match self.node(self.current) {
+            None => self.list.head,
+            Some(node) => node.next,
+        };
+        self.node(next).map(|node| &node.val)
+    }
+
+    /// Returns the previous element without moving; at the ghost this is the tail.
+    pub fn peek_prev(&self) -> Option<&'a T> {
+        let prev = match self.node(self.current) {
+            None => self.list.tail,
+            Some(node) => node.prev,
+        };
+        self.node(prev).map(|node| &node.val)
+    }
+
+    /// Returns the list this cursor points into.
+    pub fn as_list(&self) -> &'a DoublyLinkedList<T> {
+        self.list
+    }
+}
+
+/// A cursor over a `DoublyLinkedList` with editing operations.
+///
+/// Inserting, removing, splitting and splicing at the cursor are all O(1)
+/// (apart from `split_*` updating a length). Created by
+/// [`DoublyLinkedList::cursor_front_mut`] and
+/// [`DoublyLinkedList::cursor_back_mut`].
+pub struct CursorMut<'a, T> {
+    current: Link<T>,
+    index: usize,
+    list: &'a mut DoublyLinkedList<T>,
+}
+
+impl<'a, T> CursorMut<'a, T> {
+    pub(super) fn new(list: &'a mut DoublyLinkedList<T>, current: Link<T>, index: usize) -> Self {
+        CursorMut {
+            current,
+            index,
+            list,
+        }
+    }
+
+    pub(super) fn current_node(&self) -> Link<T> {
+        self.current
+    }
+
+    /// Reads the `next` link of the current node, or the head at the ghost.
+    fn next_link(&self) -> Link<T> {
+        match self.current {
+            None => self.list.head,
+            // SAFETY: the current node belongs to the exclusively borrowed list
+            Some(node) => unsafe { (*node.as_ptr()).next },
+        }
+    }
+
+    /// Reads the `prev` link of the current node, or the tail at the ghost.
+    fn prev_link(&self) -> Link<T> {
+        match self.current {
+            None => self.list.tail,
+            // SAFETY: see `next_link`.
+            Some(node) => unsafe { (*node.as_ptr()).prev },
+        }
+    }
+
+    /// Returns the value behind `link` for as long as the cursor is borrowed.
+    fn val_mut(&mut self, link: Link<T>) -> Option<&mut T> {
+        // SAFETY: the list is exclusively borrowed by the cursor, which is in
+        // turn exclusively borrowed for the lifetime of the returned reference.
+        link.map(|node| unsafe { &mut (*node.as_ptr()).val })
+    }
+
+    /// Returns the index of the current element, or `None` at the ghost.
+    pub fn index(&self) -> Option<usize> {
+        self.current.map(|_| self.index)
+    }
+
+    /// Moves to the next element; from the tail this moves to the ghost and
+    /// from the ghost to the head.
+    pub fn move_next(&mut self) {
+        match self.current {
+            None => self.index = 0,
+            Some(_) => self.index += 1,
+        }
+        self.current = self.next_link();
+    }
+
+    /// Moves to the previous element; from the head this moves to the ghost
+    /// and from the ghost to the tail.
+    pub fn move_prev(&mut self) {
+        // The ghost sits at index `len`, so the tail is always one step back
+        self.current = self.prev_link();
+        match self.current {
+            Some(_) => self.index -= 1,
+            None => self.index = self.list.len,
+        }
+    }
+
+    /// Returns the current element, or `None` at the ghost.
+    pub fn current(&mut self) -> Option<&mut T> {
+        self.val_mut(self.current)
+    }
+
+    /// Returns the next element without moving; at the ghost this is the head.
+    pub fn peek_next(&mut self) -> Option<&mut T> {
+        self.val_mut(self.next_link())
+    }
+
+    /// Returns the previous element without moving; at the ghost this is the tail.
+    pub fn peek_prev(&mut self) -> Option<&mut T> {
+        self.val_mut(self.prev_link())
+    }
+
+    /// Returns a handle to the current element, or `None` at the ghost.
+    ///
+    /// Handles to the same element compare equal, whether they come from
+    /// the cursor or from [`DoublyLinkedList::push_back_with_handle`].
+    pub fn current_handle(&mut self) -> Option<NodeHandle<T>> {
+        self.current.map(|node| self.list.handle_to(Some(node)))
+    }
+
+    /// Returns a read-only cursor pointing at the same position.
+    pub fn as_cursor(&self) -> Cursor<'_, T> {
+        Cursor::new(self.list, self.current, self.index)
+    }
+
+    /// Inserts `val` after the current element; at the ghost it becomes the
+    /// new head.
+    pub fn insert_after(&mut self, val: T) {
+        let new_node = Node::new(val);
+        let next = self.next_link();
+        // SAFETY: `current` and `next` are adjacent, the new node is unlinked
+        unsafe { self.list.link_between(self.current, next, new_node) };
+        if self.current.is_none() {
+            self.index += 1;
+        }
+    }
+
+    /// Inserts `val` before the current element; at the ghost it becomes the
+    /// new tail.
+    pub fn insert_before(&mut self, val: T) {
+        let new_node = Node::new(val);
+        let prev = self.prev_link();
+        // SAFETY: `prev` and `current` are adjacent, the new node is unlinked
+        unsafe { self.list.link_between(prev, self.current, new_node) };
+        self.index += 1;
+    }
+
+    /// Unlinks the current node and moves the cursor to the next element
+    /// (or the ghost). Returns `None` at the ghost.
+    fn unlink_current(&mut self) -> Link<T> {
+        let node = self.current?;
+        self.current = self.next_link();
+        // SAFETY: the current node belongs to the list
+        unsafe { self.list.unlink(node) };
+        Some(node)
+    }
+
+    /// Removes the current element and returns it, moving the cursor to the
+    /// next element. Returns `None` at the ghost.
+    pub fn remove_current(&mut self) -> Option<T> {
+        // SAFETY: the node was just unlinked and is not used again
+        self.unlink_current()
+            .map(|node| unsafe { Node::into_val(node) })
+    }
+
+    /// Removes the current element as a single-element list without
+    /// reallocating its node, moving the cursor to the next element.
+    ///
+    /// Like the `split_*` methods, this invalidates all of the list's
+    /// `NodeHandle`s.
+    pub fn remove_current_as_list(&mut self) -> Option<DoublyLinkedList<T>> {
+        let node = self.unlink_current()?;
+        self.list.invalidate_handles();
+        Some(DoublyLinkedList::from_chain((node, node, 1)))
+    }
+
+    /// Splits the list after the current element and returns everything
+    /// after it. At the ghost the whole list is returned.
+    ///
+    /// The list's `NodeHandle`s are invalidated, unless the whole list is
+    /// returned, in which case they stay valid for the returned list.
+    pub fn split_after(&mut self) -> DoublyLinkedList<T> {
+        let Some(node) = self.current else {
+            self.index = 0;
+            return mem::take(self.list);
+        };
+        // SAFETY: `node` and its successor belong to the list; the successor
+        // and everything after it are detached into the returned list
+        unsafe {
+            let Some(next) = (*node.as_ptr()).next.take() else {
+                return DoublyLinkedList::new();
+            };
+            (*next.as_ptr()).prev = None;
+            let split_len = self.list.len - self.index - 1;
+            let tail = self.list.tail.replace(node).unwrap();
+            self.list.len = self.index + 1;
+            self.list.invalidate_handles();
+            DoublyLinkedList::from_chain((next, tail, split_len))
+        }
+    }
+
+    /// Splits the list before the current element and returns everything
+    /// before it. At the ghost the whole list is returned.
+    ///
+    /// Invalidates `NodeHandle`s the same way as [`CursorMut::split_after`].
+    pub fn split_before(&mut self) -> DoublyLinkedList<T> {
+        let Some(node) = self.current else {
+            self.index = 0;
+            return mem::take(self.list);
+        };
+        // SAFETY: see `split_after`.
+        unsafe {
+            let Some(prev) = (*node.as_ptr()).prev.take() else {
+                return DoublyLinkedList::new();
+            };
+            (*prev.as_ptr()).next = None;
+            let split_len = mem::take(&mut self.index);
+            let head = self.list.head.replace(node).unwrap();
+            self.list.len -= split_len;
+            self.list.invalidate_handles();
+            DoublyLinkedList::from_chain((head, prev, split_len))
+        }
+    }
+
+    /// Moves every element of `other` in after the current element; at the
+    /// ghost they are put at the front of the list.
+    pub fn splice_after(&mut self, mut other: DoublyLinkedList<T>) {
+        let Some(chain) = other.take_chain() else {
+            return;
+        };
+        let next = self.next_link();
+        // SAFETY: `current` and `next` are adjacent, the chain was detached
+        // from `other`
+        unsafe { self.list.splice_between(self.current, next, chain) };
+        if self.current.is_none() {
+            self.index += chain.2;
+        }
+    }
+
+    /// Moves every element of `other` in before the current element; at the
+    /// ghost they are put at the back of the list.
+    pub fn splice_before(&mut self, mut other: DoublyLinkedList<T>) {
+        let Some(chain) = other.take_chain() else {
+            return;
+        };
+        let prev = self.prev_link();
+        // SAFETY: `prev` and `current` are adjacent, the chain was detached
+        // from `other`
+        unsafe { self.list.splice_between(prev, self.current, chain) };
+        self.index += chain.2;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dll::collect;
+
+    #[test]
+    fn test_cursor_moves_through_ghost() {
+        let list = DoublyLinkedList::from([1, 2, 3]);
+        let mut cursor = list.cursor_front();
+        assert_eq!(cursor.current(), Some(&1));
+        assert_eq!(cursor.peek_prev(), None);
+        cursor.move_next();
+        cursor.move_next();
+        assert_eq!(cursor.index(), Some(2));
+        assert_eq!(cursor.peek_next(), None);
+        cursor.move_next();
+        assert_eq!(cursor.current(), None);
+        assert_eq!(cursor.index(), None);
+        assert_eq!(cursor.peek_next(), Some(&1));
+        assert_eq!(cursor.peek_prev(), Some(&3));
+        cursor.move_prev();
+        assert_eq!(cursor.current(), Some(&3));
+
+        let mut cursor = list.cursor_back();
+        cursor.move_prev();
+        assert_eq!((cursor.index(), cursor.current()), (Some(1), Some(&2)));
+        cursor.move_prev();
+        cursor.move_prev();
+        assert_eq!(cursor.index(), None);
+        cursor.move_next();
+        assert_eq!((cursor.index(), cursor.current()), (Some(0), Some(&1)));
+    }
+
+    #[test]
+    fn test_cursor_mut_insert_and_remove() {
+        let mut list = DoublyLinkedList::from([1, 3, 5]);
+        let mut cursor = list.cursor_front_mut();
+        cursor.insert_after(2);
+        cursor.move_next();
+        cursor.move_next();
+        assert_eq!(cursor.current(), Some(&mut 3));
+        cursor.insert_before(25);
+        assert_eq!(cursor.index(), Some(3));
+        *cursor.peek_prev().unwrap() = 27;
+        assert_eq!(cursor.remove_current(), Some(3));
+        assert_eq!(cursor.current(), Some(&mut 5));
+        assert_eq!(cursor.remove_current(), Some(5));
+        assert_eq!(cursor.index(), None);
+        assert_eq!(cursor.remove_current(), None);
+        cursor.insert_after(0);
+        cursor.insert_before(6);
+        assert_eq!(collect(&list), [0, 1, 2, 27, 6]);
+    }
+
+    #[test]
+    fn test_cursor_mut_remove_ends() {
+        let mut list = DoublyLinkedList::from([1, 2, 3]);
+        let mut cursor = list.cursor_back_mut();
+        assert_eq!(cursor.remove_current(), Some(3));
+        cursor.move_next();
+        assert_eq!(cursor.remove_current(), Some(1));
+        let single = cursor.remove_current_as_list().unwrap();
+        assert_eq!(collect(&single), [2]);
+        assert!(list.is_empty());
+        assert_eq!(list.pop_front(), None);
+    }
+
+    #[test]
+    fn test_cursor_mut_split() {
+        let mut list = DoublyLinkedList::from([1, 2, 3, 4, 5]);
+        let mut cursor = list.cursor_front_mut();
+        cursor.move_next();
+        cursor.move_next();
+        let after = cursor.split_after();
+        assert_eq!(cursor.index(), Some(2));
+        let before = cursor.split_before();
+        assert_eq!(cursor.index(), Some(0));
+        assert_eq!(collect(&before), [1, 2]);
+        assert_eq!(collect(&after), [4, 5]);
+        assert_eq!(collect(&list), [3]);
+
+        let mut cursor = list.cursor_front_mut();
+        assert!(cursor.split_after().is_empty());
+        assert!(cursor.split_before().is_empty());
+        cursor.move_next();
+        assert_eq!(collect(&cursor.split_after()), [3]);
+        assert!(list.is_empty());
+    }
+
+    #[test]
+    fn test_cursor_mut_splice() {
+        let mut list = DoublyLinkedList::from([1, 4]);
+        let mut cursor = list.cursor_front_mut();
+        cursor.splice_after(DoublyLinkedList::from([2, 3]));
+        assert_eq!(cursor.index(), Some(0));
+        cursor.move_next();
+        cursor.splice_before(DoublyLinkedList::from([10, 11]));
+        assert_eq!(cursor.index(), Some(3));
+        assert_eq!(cursor.current(), Some(&mut 2));
+        cursor.splice_before(DoublyLinkedList::new());
+
+        // At the ghost, splicing goes to the ends of the list
+        let mut cursor = list.cursor_back_mut();
+        cursor.move_next();
+        cursor.splice_after(DoublyLinkedList::from([0]));
+        cursor.splice_before(DoublyLinkedList::from([5]));
+        assert_eq!(cursor.index(), None);
+        cursor.move_prev();
+        assert_eq!((cursor.index(), cursor.current()), (Some(7), Some(&mut 5)));
+        assert_eq!(cursor.as_cursor().peek_prev(), Some(&4));
+        assert_eq!(collect(&list), [0, 1, 10, 11, 2, 3, 4, 5]);
+    }
+}
@@ -0,0 +1,345 @@
+use std::iter::FusedIterator;
+use std::ops::{Bound, RangeBounds};
+use std::ptr::NonNull;
+
+use super::{DoublyLinkedList, Link, Node};
+
+impl<T> DoublyLinkedList<T> {
+    /// Keeps only the elements for which `keep` returns true, unlinking the
+    /// others in a single pass from front to back.
+    ///
+    /// `NodeHandle`s of the removed elements go stale, all others stay
+    /// valid.
+    /// ```
+    /// use dll_rs::DoublyLinkedList;
+    ///
+    /// let mut list: DoublyLinkedList<_> = (0..6).collect();
+    /// list.retain(|&x| x % 2 == 0);
+    /// assert_eq!(list, DoublyLinkedList::from([0, 2, 4]));
+    /// ```
+    pub fn retain<F>(&mut self, mut keep: F)
+    where
+        F: FnMut(&T) -> bool,
+    {
+        self.retain_mut(|val| keep(val));
+    }
+
+    /// Keeps only the elements for which `keep` returns true, passing each
+    /// one mutably. See [`DoublyLinkedList::retain`].
+    pub fn retain_mut<F>(&mut self, mut keep: F)
+    where
+        F: FnMut(&mut T) -> bool,
+    {
+        self.extract_if(|val| !keep(val)).for_each(drop);
+    }
+
+    /// Returns an iterator that removes and yields the elements for which
+    /// `filter` returns true, front to back.
+    ///
+    /// The list is only walked as far as the iterator is advanced: if it is
+    /// dropped early, the elements it has not reached yet stay in the list.
+    /// ```
+    /// use dll_rs::DoublyLinkedList;
+    ///
+    /// let mut list: DoublyLinkedList<_> = (1..=6).collect();
+    /// let evens: Vec<_> = list.extract_if(|x| *x % 2 == 0).collect();
+    /// assert_eq!(evens, [2, 4, 6]);
+    /// assert_eq!(list, DoublyLinkedList::from([1, 3, 5]));
+    /// ```
+    pub fn extract_if<F>(&mut self, filter: F) -> ExtractIf<'_, T, F>
+    where
+        F: FnMut(&mut T) -> bool,
+    {
+        ExtractIf {
+            next: self.head,
+            list: self,
+            filter,
+        }
+    }
+
+    /// Removes the elements within `range` and returns them as an iterator.
+    /// Walks to both ends of the range from whichever end of the list is
+    /// closer.
+    ///
+    /// The elements are unlinked as the iterator yields them, and the ones
+    /// left when it is dropped are removed then.
+    ///
+    /// # Panics
+    /// Panics if the range starts after it ends, or ends after the list.
+    /// ```
+    /// use dll_rs::DoublyLinkedList;
+    ///
+    /// let mut list: DoublyLinkedList<_> = (0..6).collect();
+    /// let drained: Vec<_> = list.drain(1..4).rev().collect();
+    /// assert_eq!(drained, [3, 2, 1]);
+    /// assert_eq!(list, DoublyLinkedList::from([0, 4, 5]));
+    /// ```
+    pub fn drain<R: RangeBounds<usize>>(&mut self, range: R) -> Drain<'_, T> {
+        let start = match range.start_bound() {
+            Bound::Included(&start) => start,
+            Bound::Excluded(&start) => start.saturating_add(1),
+            Bound::Unbounded => 0,
+        };
+        let end = match range.end_bound() {
+            Bound::Included(&end) => end.saturating_add(1),
+            Bound::Excluded(&end) => end,
+            Bound::Unbounded => self.len,
+        };
+        assert!(
+            start <= end && end <= self.len,
+            "cannot drain {start}..{end}, list length is {}",
+            self.len
+        );
+        let (head, tail) = if start == end {
+            (None, None)
+        } else {
+            let head = self.cursor_mut_at(start).current_node();
+            (head, self.cursor_mut_at(end - 1).current_node())
+        };
+        Drain {
+            list: self,
+            head,
+            tail,
+            len: end - start,
+        }
+    }
+
+    /// Removes consecutive repeated elements, keeping the first of each run.
+    /// ```
+    /// use dll_rs::DoublyLinkedList;
+    ///
+    /// let mut list = DoublyLinkedList::from([1, 1, 2, 3, 3, 3, 1]);
+    /// list.dedup();
+    /// assert_eq!(list, DoublyLinkedList::from([1, 2, 3, 1]));
+    /// ```
+    pub fn dedup(&mut self)
+    where
+        T: PartialEq,
+    {
+        self.dedup_by(|a, b| a == b);
+    }
+
+    /// Removes consecutive elements that map to the same key, keeping the
+    /// first of each run.
+    pub fn dedup_by_key<K, F>(&mut self, mut key: F)
+    where
+        K: PartialEq,
+        F: FnMut(&mut T) -> K,
+    {
+        self.dedup_by(|a, b| key(a) == key(b));
+    }
+
+    /// Removes consecutive elements for which `same_bucket` returns true,
+    /// keeping the first of each run.
+    ///
+    /// Like [`Vec::dedup_by`], `same_bucket(a, b)` is passed an element `a`
+    /// and the last element `b` kept before it, and `a` is removed if it
+    /// returns true.
+    pub fn dedup_by<F>(&mut self, mut same_bucket: F)
+    where
+        F: FnMut(&mut T, &mut T) -> bool,
+    {
+        let Some(mut kept) = self.head else {
+            return;
+        };
+        // SAFETY: `kept` and `node` are distinct nodes of the exclusively
+        // borrowed list, and `node` is freed right after being unlinked
+        unsafe {
+            while let Some(node) = (*kept.as_ptr()).next {
+                if same_bucket(&mut (*node.as_ptr()).val, &mut (*kept.as_ptr()).val) {
+                    drop(self.take(node));
+                } else {
+                    kept = node;
+                }
+            }
+        }
+    }
+
+    /// Unlinks `node` and moves its value out.
+    ///
+    /// # Safety
+    /// `node` must be linked into this list.
+    unsafe fn take(&mut self, node: NonNull<Node<T>>) -> T {
+        unsafe {
+            self.unlink(node);
+            Node::into_val(node)
+        }
+    }
+}
+
+/// An iterator that removes the elements of a `DoublyLinkedList` matching
+/// a filter.
+///
+/// This `struct` is created by [`DoublyLinkedList::extract_if`]. See its
+/// documentation for more.
+pub struct ExtractIf<'a, T, F> {
+    list: &'a mut DoublyLinkedList<T>,
+    next: Link<T>, // The first node not yet passed to `filter`
+    filter: F,
+}
+
+impl<T, F> Iterator for ExtractIf<'_, T, F>
+where
+    F: FnMut(&mut T) -> bool,
+{
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some(node) = self.next {
+            // SAFETY: `node` belongs to the exclusively borrowed list, and
+            // its successor is read before it is unlinked
+            unsafe {
+                self.next = (*node.as_ptr()).next;
+                if (self.filter)(&mut (*node.as_ptr()).val) {
+                    return Some(self.list.take(node));
+                }
+            }
+        }
+        None
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, Some(self.list.len))
+    }
+}
+
+impl<T, F> FusedIterator for ExtractIf<'_, T, F> where F: FnMut(&mut T) -> bool {}
+
+/// A draining iterator over a range of a `DoublyLinkedList`.
+///
+/// This `struct` is created by [`DoublyLinkedList::drain`]. See its
+/// documentation for more.
+pub struct Drain<'a, T> {
+    list: &'a mut DoublyLinkedList<T>,
+    head: Link<T>,
+    tail: Link<T>,
+    len: usize, // The number of nodes left from `head` to `tail`
+}
+
+impl<T> Iterator for Drain<'_, T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.len == 0 {
+            return None;
+        }
+        self.head.map(|node| {
+            self.len -= 1;
+            // SAFETY: `node` is within the range, which belongs to the
+            // exclusively borrowed list. Its successor is read before it is
+            // unlinked
+            unsafe {
+                self.head = (*node.as_ptr()).next;
+                self.list.take(node)
+            }
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<T> DoubleEndedIterator for Drain<'_, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.len == 0 {
+            return None;
+        }
+        self.tail.map(|node| {
+            self.len -= 1;
+            // SAFETY: see `next`.
+            unsafe {
+                self.tail = (*node.as_ptr()).prev;
+                self.list.take(node)
+            }
+        })
+    }
+}
+
+impl<T> ExactSizeIterator for Drain<'_, T> {}
+
+impl<T> FusedIterator for Drain<'_, T> {}
+
+impl<T> Drop for Drain<'_, T> {
+    fn drop(&mut self) {
+        self.for_each(drop);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dll::collect;
+
+    #[test]
+    fn test_retain_keeps_handles() {
+        let mut list = DoublyLinkedList::new();
+        let handles: Vec<_> = (0..6).map(|i| list.push_back_with_handle(i)).collect();
+        list.retain(|&x| x % 3 != 0);
+        assert_eq!(collect(&list), [1, 2, 4, 5]);
+        assert!(list.get_by_handle(&handles[3]).is_err());
+        assert_eq!(list.get_by_handle(&handles[4]), Ok(&4));
+
+        list.retain_mut(|x| {
+            *x *= 10;
+            *x > 20
+        });
+        assert_eq!(collect(&list), [40, 50]);
+        list.retain(|_| false);
+        assert!(list.is_empty());
+    }
+
+    #[test]
+    fn test_extract_if_is_lazy() {
+        let mut list: DoublyLinkedList<_> = (0..10).collect();
+        let mut odds = list.extract_if(|x| *x % 2 == 1);
+        assert_eq!(odds.next(), Some(1));
+        assert_eq!(odds.next(), Some(3));
+        assert_eq!(collect(&list), [0, 2, 4, 5, 6, 7, 8, 9]);
+
+        let big: Vec<_> = list.extract_if(|x| *x > 5).collect();
+        assert_eq!(big, [6, 7, 8, 9]);
+        assert_eq!(collect(&list), [0, 2, 4, 5]);
+    }
+
+    #[test]
+    fn test_drain() {
+        let mut list: DoublyLinkedList<_> = (0..8).collect();
+        let mut drain = list.drain(2..=5);
+        assert_eq!(drain.len(), 4);
+        assert_eq!((drain.next(), drain.next_back()), (Some(2), Some(5)));
+        drop(drain);
+        assert_eq!(collect(&list), [0, 1, 6, 7]);
+
+        assert_eq!(list.drain(4..).count(), 0);
+        assert_eq!(list.drain(..1).collect::<Vec<_>>(), [0]);
+        assert_eq!(list.drain(..).collect::<Vec<_>>(), [1, 6, 7]);
+        assert!(list.is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "cannot drain 1..3")]
+    fn test_drain_past_len_panics() {
+        DoublyLinkedList::from([1, 2]).drain(1..3);
+    }
+
+    #[test]
+    fn test_dedup() {
+        let mut list = DoublyLinkedList::from([1, 1, 2, 2, 2, 3, 1, 1]);
+        list.dedup();
+        assert_eq!(collect(&list), [1, 2, 3, 1]);
+
+        let mut list = DoublyLinkedList::from([10, 11, 25, 29, 31, 40]);
+        list.dedup_by_key(|x| *x / 10);
+        assert_eq!(collect(&list), [10, 25, 31, 40]);
+
+        // Runs are measured against the element kept, not its neighbour
+        let mut list = DoublyLinkedList::from([1, 2, 3, 4, 7, 8]);
+        list.dedup_by(|a, b| *a - *b < 3);
+        assert_eq!(collect(&list), [1, 4, 7]);
+
+        let mut empty = DoublyLinkedList::<i32>::new();
+        empty.dedup();
+        assert!(empty.is_empty());
+    }
+}
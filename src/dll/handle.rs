@@ -0,0 +1,270 @@
+use std::cell::Cell;
+use std::error::Error;
+use std::fmt;
+use std::ptr::NonNull;
+use std::rc::Rc;
+
+use super::{Cursor, CursorMut, DoublyLinkedList, HandleSlot, Link, Node};
+
+/// An opaque handle to an element of a `DoublyLinkedList`, returned by
+/// [`DoublyLinkedList::push_front_with_handle`] and
+/// [`DoublyLinkedList::push_back_with_handle`].
+///
+/// A handle does not keep its element alive. Once the element is removed,
+/// every operation taking the handle fails with [`StaleHandleError`]
+/// instead of panicking.
+///
+/// Handles are bound to their list as a whole rather than to single nodes.
+/// Moving any elements out of a list therefore invalidates *every* handle
+/// it has issued, including the handles of elements that stay behind. This
+/// is what happens to the list split by [`CursorMut::split_after`],
+/// [`CursorMut::split_before`], [`CursorMut::remove_current_as_list`] or
+/// [`DoublyLinkedList::split_off`], and to the list emptied into another by
+/// [`DoublyLinkedList::append`], [`DoublyLinkedList::merge`] or a splice.
+/// The handles of a list that only gains elements stay valid.
+pub struct NodeHandle<T> {
+    slot: HandleSlot<T>, // Cleared by `Node::into_val` when the node is freed
+    list_id: u64,
+}
+
+impl<T> Clone for NodeHandle<T> {
+    fn clone(&self) -> Self {
+        NodeHandle {
+            slot: Rc::clone(&self.slot),
+            list_id: self.list_id,
+        }
+    }
+}
+
+impl<T> PartialEq for NodeHandle<T> {
+    fn eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.slot, &other.slot) && self.list_id == other.list_id
+    }
+}
+
+impl<T> Eq for NodeHandle<T> {}
+
+impl<T> fmt::Debug for NodeHandle<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("NodeHandle")
+            .field("node", &self.slot.get())
+            .field("list_id", &self.list_id)
+            .finish()
+    }
+}
+
+/// Error returned when a [`NodeHandle`] no longer refers to an element of
+/// the list it is used with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StaleHandleError;
+
+impl fmt::Display for StaleHandleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("node handle does not refer to an element of this list")
+    }
+}
+
+impl Error for StaleHandleError {}
+
+impl<T> DoublyLinkedList<T> {
+    /// Adds an element to the front of the list and returns a handle to it.
+    pub fn push_front_with_handle(&mut self, val: T) -> NodeHandle<T> {
+        self.push_front(val);
+        self.handle_to(self.head)
+    }
+
+    /// Adds an element to the back of the list and returns a handle to it.
+    /// ```
+    /// use dll_rs::DoublyLinkedList;
+    ///
+    /// let mut list = DoublyLinkedList::new();
+    /// let one = list.push_back_with_handle(1);
+    /// let two = list.push_back_with_handle(2);
+    ///
+    /// list.move_to_front(&two).unwrap();
+    /// assert_eq!(list.remove_by_handle(&one), Ok(1));
+    /// assert!(list.remove_by_handle(&one).is_err());
+    /// assert_eq!(list.pop_front(), Some(2));
+    /// ```
+    pub fn push_back_with_handle(&mut self, val: T) -> NodeHandle<T> {
+        self.push_back(val);
+        self.handle_to(self.tail)
+    }
+
+    /// Creates a handle to a node of this list, sharing the node's slot if
+    /// it already has handles.
+    pub(super) fn handle_to(&mut self, node: Link<T>) -> NodeHandle<T> {
+        let node = node.unwrap();
+        // SAFETY: the node belongs to this list, which is exclusively borrowed
+        let slot = unsafe { &mut (*node.as_ptr()).handle };
+        let slot = Rc::clone(slot.get_or_insert_with(|| Rc::new(Cell::new(Some(node)))));
+        NodeHandle {
+            slot,
+            list_id: self.id,
+        }
+    }
+
+    /// Returns the node behind `handle` if it is still linked into this list.
+    ///
+    /// Nodes only ever leave a list by being freed, which clears the slot,
+    /// or through a split, which gives the list a new id.
+    fn resolve(&self, handle: &NodeHandle<T>) -> Result<NonNull<Node<T>>, StaleHandleError> {
+        match handle.slot.get() {
+            Some(node) if handle.list_id == self.id => Ok(node),
+            _ => Err(StaleHandleError),
+        }
+    }
+
+    /// Returns a cursor pointing at the element behind `handle`, which must
+    /// be at position `index` of the list.
+    pub(crate) fn cursor_at_handle(
+        &self,
+        handle: &NodeHandle<T>,
+        index: usize,
+    ) -> Result<Cursor<'_, T>, StaleHandleError> {
+        let node = self.resolve(handle)?;
+        debug_assert!(index < self.len);
+        Ok(Cursor::new(self, Some(node), index))
+    }
+
+    /// Returns a mutable cursor pointing at the element behind `handle`,
+    /// which must be at position `index` of the list.
+    pub(crate) fn cursor_mut_at_handle(
+        &mut self,
+        handle: &NodeHandle<T>,
+        index: usize,
+    ) -> Result<CursorMut<'_, T>, StaleHandleError> {
+        let node = self.resolve(handle)?;
+        debug_assert!(index < self.len);
+        Ok(CursorMut::new(self, Some(node), index))
+    }
+
+    /// Returns a reference to the element behind `handle`.
+    pub fn get_by_handle(&self, handle: &NodeHandle<T>) -> Result<&T, StaleHandleError> {
+        let node = self.resolve(handle)?;
+        // SAFETY: the node belongs to this list, which is borrowed for the
+        // lifetime of the returned reference.
+        Ok(unsafe { &(*node.as_ptr()).val })
+    }
+
+    /// Returns a mutable reference to the element behind `handle`.
+    pub fn get_mut_by_handle(
+        &mut self,
+        handle: &NodeHandle<T>,
+    ) -> Result<&mut T, StaleHandleError> {
+        let node = self.resolve(handle)?;
+        // SAFETY: the node belongs to this list, which is exclusively
+        // borrowed for the lifetime of the returned reference.
+        Ok(unsafe { &mut (*node.as_ptr()).val })
+    }
+
+    /// Removes the element behind `handle` in O(1) and returns it.
+    pub fn remove_by_handle(&mut self, handle: &NodeHandle<T>) -> Result<T, StaleHandleError> {
+        let node = self.resolve(handle)?;
+        // SAFETY: the node belongs to this list and is freed right after
+        // being unlinked
+        unsafe {
+            self.unlink(node);
+            Ok(Node::into_val(node))
+        }
+    }
+
+    /// Moves the element behind `handle` to the front of the list in O(1).
+    pub fn move_to_front(&mut self, handle: &NodeHandle<T>) -> Result<(), StaleHandleError> {
+        let node = self.resolve(handle)?;
+        // SAFETY: the node belongs to this list and is relinked right after
+        // being unlinked
+        unsafe {
+            self.unlink(node);
+            self.link_between(None, self.head, node);
+        }
+        Ok(())
+    }
+
+    /// Moves the element behind `handle` to the back of the list in O(1).
+    pub fn move_to_back(&mut self, handle: &NodeHandle<T>) -> Result<(), StaleHandleError> {
+        let node = self.resolve(handle)?;
+        // SAFETY: see `move_to_front`.
+        unsafe {
+            self.unlink(node);
+            self.link_between(self.tail, None, node);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_handles_get_and_remove() {
+        let mut list = DoublyLinkedList::new();
+        let a = list.push_back_with_handle("a");
+        let b = list.push_back_with_handle("b");
+        let c = list.push_front_with_handle("c");
+
+        assert_eq!(list.get_by_handle(&a), Ok(&"a"));
+        *list.get_mut_by_handle(&c).unwrap() = "z";
+        assert_eq!(list.remove_by_handle(&a), Ok("a"));
+        assert_eq!(list.remove_by_handle(&a), Err(StaleHandleError));
+        assert_eq!(list.get_by_handle(&a), Err(StaleHandleError));
+        assert_eq!(list.iter().copied().collect::<Vec<_>>(), ["z", "b"]);
+
+        assert_eq!(list.pop_back(), Some("b"));
+        assert_eq!(list.move_to_front(&b), Err(StaleHandleError));
+        assert_eq!(list.remove_by_handle(&c), Ok("z"));
+        assert!(list.is_empty());
+    }
+
+    #[test]
+    fn test_handles_move() {
+        let mut list = DoublyLinkedList::new();
+        let handles: Vec<_> = (0..4).map(|i| list.push_back_with_handle(i)).collect();
+
+        list.move_to_front(&handles[2]).unwrap();
+        list.move_to_back(&handles[0]).unwrap();
+        list.move_to_back(&handles[0]).unwrap();
+        list.move_to_front(&handles[2]).unwrap();
+        assert_eq!(list.iter().copied().collect::<Vec<_>>(), [2, 1, 3, 0]);
+        assert_eq!(list.iter().rev().copied().collect::<Vec<_>>(), [0, 3, 1, 2]);
+        assert_eq!(list.pop_front(), Some(2));
+        assert_eq!(list.pop_back(), Some(0));
+    }
+
+    #[test]
+    fn test_cursor_handles_share_the_node_slot() {
+        let mut list = DoublyLinkedList::from([1, 2]);
+        let b = list.push_back_with_handle(3);
+        let mut cursor = list.cursor_back_mut();
+        assert_eq!(cursor.current_handle(), Some(b.clone()));
+        cursor.move_prev();
+        let a = cursor.current_handle().unwrap();
+        cursor.move_next();
+        cursor.move_next();
+        assert_eq!(cursor.current_handle(), None);
+
+        assert_eq!(list.remove_by_handle(&a), Ok(2));
+        assert_eq!(list.remove_by_handle(&b), Ok(3));
+        assert_eq!(list.get_by_handle(&a), Err(StaleHandleError));
+    }
+
+    #[test]
+    fn test_handles_are_bound_to_their_list() {
+        let mut list = DoublyLinkedList::new();
+        let mut other = DoublyLinkedList::new();
+        let a = list.push_back_with_handle(1);
+        let b = other.push_back_with_handle(2);
+        assert_eq!(list.get_by_handle(&b), Err(StaleHandleError));
+        assert_eq!(other.remove_by_handle(&a), Err(StaleHandleError));
+
+        // Nodes split off into a new list are no longer reachable through
+        // the old list's handles
+        let c = list.push_back_with_handle(3);
+        let mut cursor = list.cursor_front_mut();
+        let split = cursor.split_after();
+        assert_eq!(list.get_by_handle(&c), Err(StaleHandleError));
+        assert_eq!(list.get_by_handle(&a), Err(StaleHandleError));
+        assert_eq!(split.len(), 1);
+    }
+}
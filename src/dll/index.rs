@@ -0,0 +1,195 @@
+use std::error::Error;
+use std::fmt;
+
+use super::{CursorMut, DoublyLinkedList};
+
+/// Error returned by the `try_*` positional methods when the index is past
+/// the end of the list.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IndexOutOfBoundsError {
+    pub index: usize,
+    pub len: usize,
+}
+
+impl fmt::Display for IndexOutOfBoundsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "index {} is out of bounds for a list of length {}",
+            self.index, self.len
+        )
+    }
+}
+
+impl Error for IndexOutOfBoundsError {}
+
+impl<T> DoublyLinkedList<T> {
+    /// Returns a reference to the element at `index`, or `None` if it is out
+    /// of bounds. Walks from whichever end of the list is closer.
+    /// ```
+    /// use dll_rs::DoublyLinkedList;
+    ///
+    /// let list = DoublyLinkedList::from(['a', 'b', 'c']);
+    /// assert_eq!(list.get(1), Some(&'b'));
+    /// assert_eq!(list.get(3), None);
+    /// ```
+    pub fn get(&self, index: usize) -> Option<&T> {
+        if index >= self.len {
+            None
+        } else if index < self.len / 2 {
+            self.iter().nth(index)
+        } else {
+            self.iter().rev().nth(self.len - index - 1)
+        }
+    }
+
+    /// Returns a mutable reference to the element at `index`, or `None` if
+    /// it is out of bounds. Walks from whichever end of the list is closer.
+    pub fn get_mut(&mut self, index: usize) -> Option<&mut T> {
+        let len = self.len;
+        if index >= len {
+            None
+        } else if index < len / 2 {
+            self.iter_mut().nth(index)
+        } else {
+            self.iter_mut().rev().nth(len - index - 1)
+        }
+    }
+
+    /// Inserts `val` at `index`, shifting everything after it towards the
+    /// back. Walks from whichever end of the list is closer.
+    ///
+    /// # Panics
+    /// Panics if `index > len`; see [`DoublyLinkedList::try_insert`].
+    /// ```
+    /// use dll_rs::DoublyLinkedList;
+    ///
+    /// let mut list = DoublyLinkedList::from([1, 3]);
+    /// list.insert(1, 2);
+    /// list.insert(3, 4);
+    /// assert_eq!(list, DoublyLinkedList::from([1, 2, 3, 4]));
+    /// ```
+    pub fn insert(&mut self, index: usize, val: T) {
+        if let Err(err) = self.try_insert(index, val) {
+            panic!("{err}");
+        }
+    }
+
+    /// Inserts `val` at `index`, or returns an error if `index > len`, in
+    /// which case `val` is dropped.
+    pub fn try_insert(&mut self, index: usize, val: T) -> Result<(), IndexOutOfBoundsError> {
+        if index > self.len {
+            return Err(self.out_of_bounds(index));
+        }
+        // The element at `index` (or the ghost at `len`) ends up after `val`
+        self.cursor_mut_at(index).insert_before(val);
+        Ok(())
+    }
+
+    /// Removes the element at `index` and returns it. Walks from whichever
+    /// end of the list is closer.
+    ///
+    /// # Panics
+    /// Panics if `index >= len`; see [`DoublyLinkedList::try_remove`].
+    pub fn remove(&mut self, index: usize) -> T {
+        match self.try_remove(index) {
+            Ok(val) => val,
+            Err(err) => panic!("{err}"),
+        }
+    }
+
+    /// Removes the element at `index` and returns it, or returns an error if
+    /// `index >= len`.
+    pub fn try_remove(&mut self, index: usize) -> Result<T, IndexOutOfBoundsError> {
+        if index >= self.len {
+            return Err(self.out_of_bounds(index));
+        }
+        Ok(self.cursor_mut_at(index).remove_current().unwrap())
+    }
+
+    fn out_of_bounds(&self, index: usize) -> IndexOutOfBoundsError {
+        IndexOutOfBoundsError {
+            index,
+            len: self.len,
+        }
+    }
+
+    /// Returns a cursor at `index`, or at the ghost for `index == len`,
+    /// starting from the closer end.
+    pub(super) fn cursor_mut_at(&mut self, index: usize) -> CursorMut<'_, T> {
+        let len = self.len;
+        if index < len / 2 {
+            let mut cursor = self.cursor_front_mut();
+            (0..index).for_each(|_| cursor.move_next());
+            cursor
+        } else {
+            let mut cursor = self.cursor_back_mut();
+            if index == len {
+                cursor.move_next();
+            }
+            (index..len.saturating_sub(1)).for_each(|_| cursor.move_prev());
+            cursor
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_get_from_both_halves() {
+        let mut list: DoublyLinkedList<_> = (0..5).collect();
+        for i in 0..5 {
+            assert_eq!(list.get(i), Some(&i));
+        }
+        assert_eq!(list.get(5), None);
+        assert_eq!(list.get(usize::MAX), None);
+
+        *list.get_mut(1).unwrap() = 10;
+        *list.get_mut(3).unwrap() = 30;
+        assert_eq!(list.get_mut(5), None);
+        assert_eq!(list, DoublyLinkedList::from([0, 10, 2, 30, 4]));
+        assert_eq!(DoublyLinkedList::<i32>::new().get(0), None);
+    }
+
+    #[test]
+    fn test_insert_and_remove() {
+        let mut list = DoublyLinkedList::new();
+        list.insert(0, 2);
+        list.insert(0, 0);
+        list.insert(1, 1);
+        list.insert(3, 4);
+        list.insert(3, 3);
+        assert_eq!(list, DoublyLinkedList::from([0, 1, 2, 3, 4]));
+        assert_eq!(list.iter().rev().count(), 5);
+
+        assert_eq!(list.remove(3), 3);
+        assert_eq!(list.remove(0), 0);
+        assert_eq!(list.remove(2), 4);
+        assert_eq!(list, DoublyLinkedList::from([1, 2]));
+        assert_eq!(list.back(), Some(&2));
+    }
+
+    #[test]
+    fn test_out_of_bounds() {
+        let mut list = DoublyLinkedList::from([1]);
+        let err = IndexOutOfBoundsError { index: 2, len: 1 };
+        assert_eq!(list.try_insert(2, 0), Err(err));
+        assert_eq!(
+            list.try_remove(1),
+            Err(IndexOutOfBoundsError { index: 1, len: 1 })
+        );
+        assert_eq!(list.try_remove(0), Ok(1));
+        assert_eq!(
+            err.to_string(),
+            "index 2 is out of bounds for a list of length 1"
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "out of bounds")]
+    fn test_remove_panics_out_of_bounds() {
+        DoublyLinkedList::<i32>::new().remove(0);
+    }
+}
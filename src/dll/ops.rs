@@ -0,0 +1,201 @@
+use std::mem;
+
+use super::DoublyLinkedList;
+
+impl<T> DoublyLinkedList<T> {
+    /// Removes all elements from the list.
+    pub fn clear(&mut self) {
+        // Pop all elements to ensure nodes are deallocated iteratively
+        while self.pop_front().is_some() {}
+    }
+
+    /// Moves all elements of `other` to the back of the list in O(1),
+    /// leaving `other` empty.
+    ///
+    /// `NodeHandle`s issued by `other` are invalidated, while the ones issued
+    /// by `self` stay valid.
+    /// ```
+    /// use dll_rs::DoublyLinkedList;
+    ///
+    /// let mut list = DoublyLinkedList::from([1, 2]);
+    /// let mut other = DoublyLinkedList::from([3, 4]);
+    /// list.append(&mut other);
+    /// assert_eq!(list, DoublyLinkedList::from([1, 2, 3, 4]));
+    /// assert!(other.is_empty());
+    /// ```
+    pub fn append(&mut self, other: &mut Self) {
+        if let Some(chain) = other.take_chain() {
+            // SAFETY: the chain was detached from `other`
+            unsafe { self.splice_between(self.tail, None, chain) };
+        }
+    }
+
+    /// Splits the list in two at `at`, returning everything from `at`
+    /// onwards. Walks from whichever end of the list is closer.
+    ///
+    /// Like [`CursorMut::split_after`](super::CursorMut::split_after), this
+    /// invalidates the list's `NodeHandle`s unless the whole list moves.
+    ///
+    /// # Panics
+    /// Panics if `at > len`.
+    pub fn split_off(&mut self, at: usize) -> Self {
+        assert!(
+            at <= self.len,
+            "cannot split off at {at}, list length is {}",
+            self.len
+        );
+        match at {
+            0 => mem::take(self),
+            at => self.cursor_mut_at(at - 1).split_after(),
+        }
+    }
+
+    /// Reverses the order of the elements in place by swapping the `next`
+    /// and `prev` links of every node. No values are moved.
+    pub fn reverse(&mut self) {
+        let mut current = self.head;
+        while let Some(node) = current {
+            // SAFETY: every node reachable from the head belongs to the
+            // exclusively borrowed list
+            unsafe {
+                let node = &mut *node.as_ptr();
+                mem::swap(&mut node.next, &mut node.prev);
+                current = node.prev;
+            }
+        }
+        mem::swap(&mut self.head, &mut self.tail);
+    }
+
+    /// Rotates the list `n` places to the left, so the element at index `n`
+    /// becomes the head. Only relinks the two ends; walks from whichever end
+    /// of the list is closer to `n`.
+    ///
+    /// # Panics
+    /// Panics if `n > len`.
+    /// ```
+    /// use dll_rs::DoublyLinkedList;
+    ///
+    /// let mut list: DoublyLinkedList<_> = (0..5).collect();
+    /// list.rotate_left(2);
+    /// assert_eq!(list, DoublyLinkedList::from([2, 3, 4, 0, 1]));
+    /// list.rotate_right(3);
+    /// assert_eq!(list, DoublyLinkedList::from([4, 0, 1, 2, 3]));
+    /// ```
+    pub fn rotate_left(&mut self, n: usize) {
+        assert!(
+            n <= self.len,
+            "cannot rotate by {n}, list length is {}",
+            self.len
+        );
+        if n == 0 || n == self.len {
+            return;
+        }
+        let new_head = self.cursor_mut_at(n).current_node().unwrap();
+        // SAFETY: all four nodes belong to the exclusively borrowed list, and
+        // `new_tail`/`new_head` are adjacent since 0 < n < len
+        unsafe {
+            let new_tail = (*new_head.as_ptr()).prev.take().unwrap();
+            (*new_tail.as_ptr()).next = None;
+            // Close the ring between the old tail and head, which was opened
+            // up in front of the new head above
+            let old_head = self.head.replace(new_head).unwrap();
+            let old_tail = self.tail.replace(new_tail).unwrap();
+            (*old_head.as_ptr()).prev = Some(old_tail);
+            (*old_tail.as_ptr()).next = Some(old_head);
+        }
+    }
+
+    /// Rotates the list `n` places to the right, so the element at index
+    /// `len - n` becomes the head.
+    ///
+    /// # Panics
+    /// Panics if `n > len`.
+    pub fn rotate_right(&mut self, n: usize) {
+        assert!(
+            n <= self.len,
+            "cannot rotate by {n}, list length is {}",
+            self.len
+        );
+        self.rotate_left(self.len - n);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dll::collect;
+
+    #[test]
+    fn test_append() {
+        let mut list = DoublyLinkedList::new();
+        let mut other = DoublyLinkedList::from([1, 2]);
+        list.append(&mut other);
+        list.append(&mut DoublyLinkedList::new());
+        list.append(&mut DoublyLinkedList::from([3]));
+        assert_eq!(collect(&list), [1, 2, 3]);
+        assert!(other.is_empty());
+        other.push_back(4);
+        assert_eq!(collect(&other), [4]);
+    }
+
+    #[test]
+    fn test_split_off() {
+        let mut list: DoublyLinkedList<_> = (0..6).collect();
+        let back = list.split_off(4);
+        let middle = list.split_off(1);
+        assert_eq!(collect(&list), [0]);
+        assert_eq!(collect(&middle), [1, 2, 3]);
+        assert_eq!(collect(&back), [4, 5]);
+        assert!(list.split_off(1).is_empty());
+        assert_eq!(collect(&list.split_off(0)), [0]);
+        assert!(list.is_empty());
+    }
+
+    #[test]
+    fn test_clear_invalidates_handles() {
+        let mut list = DoublyLinkedList::new();
+        let handle = list.push_back_with_handle(1);
+        list.push_back(2);
+        list.clear();
+        assert!(list.is_empty());
+        assert!(list.get_by_handle(&handle).is_err());
+        list.push_front(3);
+        assert_eq!(collect(&list), [3]);
+    }
+
+    #[test]
+    fn test_reverse() {
+        let mut list = DoublyLinkedList::new();
+        list.reverse();
+        assert!(list.is_empty());
+
+        let handle = list.push_back_with_handle(1);
+        list.extend([2, 3]);
+        list.reverse();
+        assert_eq!(collect(&list), [3, 2, 1]);
+        list.move_to_front(&handle).unwrap();
+        list.push_back(0);
+        assert_eq!(collect(&list), [1, 3, 2, 0]);
+    }
+
+    #[test]
+    fn test_rotate() {
+        let mut list: DoublyLinkedList<_> = (0..5).collect();
+        list.rotate_left(0);
+        list.rotate_right(5);
+        assert_eq!(collect(&list), [0, 1, 2, 3, 4]);
+        list.rotate_left(1);
+        assert_eq!(collect(&list), [1, 2, 3, 4, 0]);
+        list.rotate_right(2);
+        assert_eq!(collect(&list), [4, 0, 1, 2, 3]);
+        list.rotate_left(4);
+        assert_eq!(collect(&list), [3, 4, 0, 1, 2]);
+        assert_eq!((list.pop_front(), list.pop_back()), (Some(3), Some(2)));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_rotate_past_len_panics() {
+        DoublyLinkedList::from([1]).rotate_left(2);
+    }
+}
@@ -0,0 +1,73 @@
+use std::fmt;
+use std::marker::PhantomData;
+
+use serde::de::{Deserialize, Deserializer, SeqAccess, Visitor};
+use serde::ser::{Serialize, Serializer};
+
+use super::DoublyLinkedList;
+
+impl<T: Serialize> Serialize for DoublyLinkedList<T> {
+    /// Serializes the list as a sequence of its elements, front to back.
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self)
+    }
+}
+
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for DoublyLinkedList<T> {
+    /// Deserializes a sequence into a list, pushing every element to the
+    /// back as soon as it is read, without buffering them first.
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_seq(ListVisitor(PhantomData))
+    }
+}
+
+struct ListVisitor<T>(PhantomData<T>);
+
+impl<'de, T: Deserialize<'de>> Visitor<'de> for ListVisitor<T> {
+    type Value = DoublyLinkedList<T>;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("a sequence")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let mut list = DoublyLinkedList::new();
+        while let Some(val) = seq.next_element()? {
+            list.push_back(val);
+        }
+        Ok(list)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_json_round_trip() {
+        let list = DoublyLinkedList::from([(1, "a".to_string()), (2, "b".to_string())]);
+        let json = serde_json::to_string(&list).unwrap();
+        assert_eq!(json, r#"[[1,"a"],[2,"b"]]"#);
+        let back: DoublyLinkedList<(i32, String)> = serde_json::from_str(&json).unwrap();
+        assert_eq!(back, list);
+        assert_eq!(back.back(), Some(&(2, "b".to_string())));
+
+        let empty: DoublyLinkedList<u8> = serde_json::from_str("[]").unwrap();
+        assert!(empty.is_empty());
+        assert!(serde_json::from_str::<DoublyLinkedList<u8>>(r#"{"a":1}"#).is_err());
+    }
+
+    #[test]
+    fn test_binary_round_trip() {
+        let nested: DoublyLinkedList<DoublyLinkedList<u32>> =
+            (0..4).map(|i| (0..i).collect()).collect();
+        let bytes = bincode::serialize(&nested).unwrap();
+        let back: DoublyLinkedList<DoublyLinkedList<u32>> = bincode::deserialize(&bytes).unwrap();
+        assert_eq!(back, nested);
+
+        // A list serializes exactly like the `Vec` it replaces
+        let vec: Vec<Vec<u32>> = bincode::deserialize(&bytes).unwrap();
+        assert_eq!(vec, [vec![], vec![0], vec![0, 1], vec![0, 1, 2]]);
+        assert!(bincode::deserialize::<DoublyLinkedList<u32>>(&bytes[..3]).is_err());
+    }
+}
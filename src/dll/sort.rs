@@ -0,0 +1,332 @@
+use std::cmp::Ordering;
+use std::mem;
+
+use super::DoublyLinkedList;
+
+/// The sorted runs of a merge sort in progress.
+///
+/// Every element is always owned by exactly one of the lists below, and
+/// dropping the runs moves them all back into `list`. If a comparison
+/// panics, the list therefore keeps all of its elements, in some order.
+struct Runs<'a, T> {
+    list: &'a mut DoublyLinkedList<T>,
+    bins: Vec<DoublyLinkedList<T>>, // `bins[i]` is empty or holds 2^i elements
+    carry: DoublyLinkedList<T>,
+    merged: DoublyLinkedList<T>,
+}
+
+impl<'a, T> Runs<'a, T> {
+    fn new(list: &'a mut DoublyLinkedList<T>) -> Self {
+        Runs {
+            list,
+            bins: Vec::new(),
+            carry: DoublyLinkedList::new(),
+            merged: DoublyLinkedList::new(),
+        }
+    }
+}
+
+impl<T> Drop for Runs<'_, T> {
+    fn drop(&mut self) {
+        self.carry.move_all_to(self.list);
+        self.merged.move_all_to(self.list);
+        for bin in &mut self.bins {
+            bin.move_all_to(self.list);
+        }
+    }
+}
+
+impl<T> DoublyLinkedList<T> {
+    /// Sorts the list with a stable merge sort.
+    ///
+    /// Nodes are relinked rather than values moved, so sorting takes
+    /// O(n log n) comparisons but no allocation beyond O(log n) run heads,
+    /// and every `NodeHandle` stays valid.
+    /// ```
+    /// use dll_rs::DoublyLinkedList;
+    ///
+    /// let mut list = DoublyLinkedList::from([3, 1, 2]);
+    /// let zero = list.push_back_with_handle(0);
+    /// list.sort();
+    /// assert_eq!(list, DoublyLinkedList::from([0, 1, 2, 3]));
+    /// assert_eq!(list.get_by_handle(&zero), Ok(&0));
+    /// ```
+    pub fn sort(&mut self)
+    where
+        T: Ord,
+    {
+        self.merge_sort(&mut T::lt);
+    }
+
+    /// Sorts the list with a stable merge sort, ordering elements by
+    /// `compare`. See [`DoublyLinkedList::sort`].
+    ///
+    /// If `compare` panics, the list keeps all of its elements in an
+    /// unspecified order.
+    pub fn sort_by<F>(&mut self, mut compare: F)
+    where
+        F: FnMut(&T, &T) -> Ordering,
+    {
+        self.merge_sort(&mut |a, b| compare(a, b) == Ordering::Less);
+    }
+
+    /// Sorts the list with a stable merge sort, ordering elements by the
+    /// key `f` extracts. See [`DoublyLinkedList::sort`].
+    /// ```
+    /// use dll_rs::DoublyLinkedList;
+    ///
+    /// let mut list = DoublyLinkedList::from(["bb", "a", "cc", "d"]);
+    /// list.sort_by_key(|s| s.len());
+    /// assert_eq!(list, DoublyLinkedList::from(["a", "d", "bb", "cc"]));
+    /// ```
+    pub fn sort_by_key<K, F>(&mut self, mut f: F)
+    where
+        K: Ord,
+        F: FnMut(&T) -> K,
+    {
+        self.merge_sort(&mut |a, b| f(a) < f(b));
+    }
+
+    /// Sorts the list. Provided for parity with slices: a merge sort over
+    /// linked nodes is stable at no extra cost, so this is the same as
+    /// [`DoublyLinkedList::sort`].
+    pub fn sort_unstable(&mut self)
+    where
+        T: Ord,
+    {
+        self.sort();
+    }
+
+    /// Sorts the list by `compare`, the same as [`DoublyLinkedList::sort_by`].
+    pub fn sort_unstable_by<F>(&mut self, compare: F)
+    where
+        F: FnMut(&T, &T) -> Ordering,
+    {
+        self.sort_by(compare);
+    }
+
+    /// Sorts the list by the key `f` extracts, the same as
+    /// [`DoublyLinkedList::sort_by_key`].
+    pub fn sort_unstable_by_key<K, F>(&mut self, f: F)
+    where
+        K: Ord,
+        F: FnMut(&T) -> K,
+    {
+        self.sort_by_key(f);
+    }
+
+    /// Returns true if the elements are in ascending order.
+    pub fn is_sorted(&self) -> bool
+    where
+        T: PartialOrd,
+    {
+        self.is_sorted_by(|a, b| a <= b)
+    }
+
+    /// Returns true if `compare` holds for every pair of neighbouring
+    /// elements.
+    pub fn is_sorted_by<F>(&self, mut compare: F) -> bool
+    where
+        F: FnMut(&T, &T) -> bool,
+    {
+        let mut iter = self.iter();
+        let Some(mut prev) = iter.next() else {
+            return true;
+        };
+        iter.all(|next| compare(mem::replace(&mut prev, next), next))
+    }
+
+    /// Returns true if the keys `f` extracts are in ascending order.
+    pub fn is_sorted_by_key<K, F>(&self, mut f: F) -> bool
+    where
+        K: PartialOrd,
+        F: FnMut(&T) -> K,
+    {
+        self.is_sorted_by(|a, b| f(a) <= f(b))
+    }
+
+    /// Merges the sorted list `other` into this sorted list in O(n + m),
+    /// leaving `other` empty. Equal elements of `self` come first.
+    ///
+    /// `NodeHandle`s issued by `other` are invalidated, while the ones issued
+    /// by `self` stay valid.
+    /// ```
+    /// use dll_rs::DoublyLinkedList;
+    ///
+    /// let mut list = DoublyLinkedList::from([1, 4, 5]);
+    /// let mut other = DoublyLinkedList::from([2, 3, 6]);
+    /// list.merge(&mut other);
+    /// assert_eq!(list, DoublyLinkedList::from([1, 2, 3, 4, 5, 6]));
+    /// assert!(other.is_empty());
+    /// ```
+    pub fn merge(&mut self, other: &mut Self)
+    where
+        T: Ord,
+    {
+        // Up front, as a panicking comparison can leave nodes of `other`
+        // in this list
+        other.invalidate_handles();
+        let mut runs = Runs::new(self);
+        runs.list.move_all_to(&mut runs.carry);
+        let Runs { carry, merged, .. } = &mut runs;
+        merged.merge_runs(carry, other, &mut T::lt);
+        mem::swap(carry, merged);
+    }
+
+    /// Bottom-up merge sort over nodes. Each element is merged into a
+    /// binary counter of runs of doubling length, then the runs are merged
+    /// from shortest to longest.
+    fn merge_sort<F>(&mut self, is_less: &mut F)
+    where
+        F: FnMut(&T, &T) -> bool,
+    {
+        if self.len < 2 {
+            return;
+        }
+        let mut runs = Runs::new(self);
+        let Runs {
+            list,
+            bins,
+            carry,
+            merged,
+        } = &mut runs;
+        while list.head.is_some() {
+            list.move_front_to(carry);
+            let mut i = 0;
+            // Longer runs hold earlier elements, so they go first on ties
+            while let Some(bin) = bins.get_mut(i).filter(|bin| !bin.is_empty()) {
+                merged.merge_runs(bin, carry, is_less);
+                mem::swap(carry, merged);
+                i += 1;
+            }
+            if i == bins.len() {
+                bins.push(DoublyLinkedList::new());
+            }
+            mem::swap(&mut bins[i], carry);
+        }
+        for bin in bins.iter_mut() {
+            merged.merge_runs(bin, carry, is_less);
+            mem::swap(carry, merged);
+        }
+        // Dropping `runs` moves the sorted `carry` back into the list
+    }
+
+    /// Moves the elements of the sorted `left` and `right` to the back of
+    /// this list in order, taking from `left` on ties.
+    fn merge_runs<F>(&mut self, left: &mut Self, right: &mut Self, is_less: &mut F)
+    where
+        F: FnMut(&T, &T) -> bool,
+    {
+        while let (Some(l), Some(r)) = (left.head, right.head) {
+            // SAFETY: both nodes belong to lists that are borrowed here, and
+            // no node is moved before the comparison returns
+            let take_right = unsafe { is_less(&(*r.as_ptr()).val, &(*l.as_ptr()).val) };
+            if take_right {
+                right.move_front_to(self);
+            } else {
+                left.move_front_to(self);
+            }
+        }
+        left.move_all_to(self);
+        right.move_all_to(self);
+    }
+
+    /// Moves the head node to the back of `dst` without reallocating it.
+    fn move_front_to(&mut self, dst: &mut Self) {
+        if let Some(node) = self.head {
+            // SAFETY: the head belongs to this list, and is linked into `dst`
+            // right after being unlinked
+            unsafe {
+                self.unlink(node);
+                dst.link_between(dst.tail, None, node);
+            }
+        }
+    }
+
+    /// Moves every node to the back of `dst`. Unlike `append`, this keeps
+    /// the list's id, so a sort can hand its nodes back afterwards without
+    /// invalidating any handles.
+    fn move_all_to(&mut self, dst: &mut Self) {
+        if let (Some(head), Some(tail)) = (self.head.take(), self.tail.take()) {
+            let len = mem::take(&mut self.len);
+            // SAFETY: the chain was detached from this list
+            unsafe { dst.splice_between(dst.tail, None, (head, tail, len)) };
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dll::collect;
+    use std::panic::{self, AssertUnwindSafe};
+
+    #[test]
+    fn test_sort_is_stable() {
+        for len in [0, 1, 2, 7, 64, 100] {
+            // Pseudo-random keys with plenty of duplicates
+            let vals: Vec<_> = (0..len).map(|i| ((i * 37 + 11) % 13, i)).collect();
+            let mut list: DoublyLinkedList<_> = vals.iter().copied().collect();
+            list.sort_by_key(|&(key, _)| key);
+            let mut expected = vals.clone();
+            expected.sort_by_key(|&(key, _)| key);
+            assert_eq!(collect(&list), expected);
+            assert!(list.is_sorted_by_key(|&(key, _)| key));
+
+            list.sort_unstable_by(|a, b| b.cmp(a));
+            expected.sort_by(|a, b| b.cmp(a));
+            assert_eq!(collect(&list), expected);
+            assert!(!list.is_sorted() || len < 2);
+        }
+    }
+
+    #[test]
+    fn test_sort_keeps_handles() {
+        let mut list = DoublyLinkedList::new();
+        let handles: Vec<_> = [5, 3, 9, 1]
+            .into_iter()
+            .map(|i| list.push_back_with_handle(i))
+            .collect();
+        list.sort_unstable();
+        assert_eq!(collect(&list), [1, 3, 5, 9]);
+        assert_eq!(list.get_by_handle(&handles[2]), Ok(&9));
+        list.move_to_front(&handles[2]).unwrap();
+        assert_eq!(collect(&list), [9, 1, 3, 5]);
+    }
+
+    #[test]
+    fn test_merge() {
+        let mut list = DoublyLinkedList::from([(1, 'a'), (3, 'a')]);
+        let kept = list.push_back_with_handle((5, 'a'));
+        let mut other = DoublyLinkedList::from([(0, 'b'), (3, 'b')]);
+        let moved = other.push_back_with_handle((9, 'b'));
+        list.merge(&mut other);
+        assert_eq!(
+            collect(&list),
+            [(0, 'b'), (1, 'a'), (3, 'a'), (3, 'b'), (5, 'a'), (9, 'b')]
+        );
+        assert!(other.is_empty());
+        assert_eq!(list.get_by_handle(&kept), Ok(&(5, 'a')));
+        assert!(list.get_by_handle(&moved).is_err());
+
+        list.merge(&mut DoublyLinkedList::new());
+        assert_eq!(list.len(), 6);
+    }
+
+    #[test]
+    fn test_panicking_compare_keeps_elements() {
+        let mut list: DoublyLinkedList<_> = (0..50).rev().collect();
+        let mut calls = 0;
+        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+            list.sort_by(|a, b| {
+                calls += 1;
+                assert!(calls < 100, "comparison failed");
+                a.cmp(b)
+            })
+        }));
+        assert!(result.is_err());
+        let mut vals = collect(&list);
+        vals.sort();
+        assert_eq!(vals, (0..50).collect::<Vec<_>>());
+    }
+}
@@ -0,0 +1,923 @@
+//! A `DoublyLinkedList` that survives crashes through a write-ahead log.
+//!
+//! A [`DurableList`] lives in a directory of its own, holding a snapshot of
+//! the list (see the [`snapshot`](crate::snapshot) module) and a log of the
+//! mutations made since. Both carry a generation number in their names,
+//! `snapshot.<gen>` and `wal.<gen>`, and the log of a generation applies on
+//! top of the snapshot of the same generation. Generation 0 starts out
+//! without a snapshot, from an empty list.
+//!
+//! Every mutation is appended to the log as one record before the list is
+//! changed. A record is laid out as follows, with every integer
+//! little-endian:
+//!
+//! | field        | size    | contents                                 |
+//! |--------------|---------|------------------------------------------|
+//! | length       | 4 bytes | the length of the payload                |
+//! | length check | 4 bytes | CRC-32 (IEEE) of the length field        |
+//! | checksum     | 4 bytes | CRC-32 (IEEE) of the payload             |
+//! | payload      | length  | a 1 byte operation, then its arguments   |
+//!
+//! The length carries a checksum of its own, so that a damaged length is
+//! told apart from a record cut short by a crash.
+//!
+//! where the operations are
+//!
+//! | operation   | byte | arguments                                   |
+//! |-------------|------|---------------------------------------------|
+//! | push front  | 0    | the element's [`Codec`] encoding            |
+//! | push back   | 1    | the element's [`Codec`] encoding            |
+//! | pop front   | 2    |                                             |
+//! | pop back    | 3    |                                             |
+//! | insert      | 4    | the index as 8 bytes, then the encoding     |
+//! | remove      | 5    | the index as 8 bytes                        |
+//! | clear       | 6    |                                             |
+//!
+//! Once the log outgrows a threshold it is compacted: the list is written
+//! to the snapshot of the next generation, and the log of that generation
+//! starts out empty. The files of the previous generation are only removed
+//! once the new ones are durable, so a crash at any point leaves one
+//! complete generation behind.
+
+use std::error::Error;
+use std::fmt;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufRead, BufReader, BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+use crate::snapshot::{Codec, Crc32, SnapshotError};
+use crate::{DoublyLinkedList, Iter};
+
+/// The size of the log past which [`DurableList::open`] compacts it.
+pub const DEFAULT_COMPACTION_THRESHOLD: u64 = 4 << 20;
+
+const HEADER_LEN: usize = 12;
+
+const PUSH_FRONT: u8 = 0;
+const PUSH_BACK: u8 = 1;
+const POP_FRONT: u8 = 2;
+const POP_BACK: u8 = 3;
+const INSERT: u8 = 4;
+const REMOVE: u8 = 5;
+const CLEAR: u8 = 6;
+
+/// When the log is flushed to disk with `fsync`.
+///
+/// Every record is handed to the operating system as soon as it is written,
+/// so a crash of the process alone never loses a mutation. The policy only
+/// decides how many of them a power loss or a crash of the system may take
+/// along.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SyncPolicy {
+    /// Sync after every mutation. The slowest, but nothing that returned is
+    /// ever lost.
+    Always,
+    /// Sync after every `n` mutations.
+    EveryN(usize),
+    /// Only sync on [`DurableList::sync`] and when compacting.
+    Manual,
+}
+
+/// Error returned when a `DurableList` could not be opened.
+#[derive(Debug)]
+pub enum OpenError {
+    /// Reading or writing the directory failed.
+    Io(io::Error),
+    /// The snapshot could not be read.
+    Snapshot(SnapshotError),
+    /// The log record at `index` has a valid checksum, but could not be
+    /// applied to the list. The log was likely written for a different type.
+    InvalidRecord { index: u64 },
+    /// The log record at `index` has a checksum that does not match, and
+    /// is followed by more records, so it is not the torn end of the log.
+    CorruptRecord { index: u64 },
+}
+
+impl fmt::Display for OpenError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OpenError::Io(err) => write!(f, "failed to open durable list: {err}"),
+            OpenError::Snapshot(err) => err.fmt(f),
+            OpenError::InvalidRecord { index } => {
+                write!(f, "record {index} of the log could not be applied")
+            }
+            OpenError::CorruptRecord { index } => {
+                write!(f, "record {index} of the log is corrupted")
+            }
+        }
+    }
+}
+
+impl Error for OpenError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            OpenError::Io(err) => Some(err),
+            OpenError::Snapshot(err) => Some(err),
+            OpenError::InvalidRecord { .. } | OpenError::CorruptRecord { .. } => None,
+        }
+    }
+}
+
+impl From<io::Error> for OpenError {
+    fn from(err: io::Error) -> Self {
+        OpenError::Io(err)
+    }
+}
+
+impl From<SnapshotError> for OpenError {
+    fn from(err: SnapshotError) -> Self {
+        OpenError::Snapshot(err)
+    }
+}
+
+/// Error returned when an element could not be removed from a
+/// `DurableList`, or was removed but the log could not be synced or
+/// compacted afterwards.
+pub struct RemoveError<T> {
+    error: io::Error,
+    removed: Option<T>,
+}
+
+impl<T> RemoveError<T> {
+    /// Returns the error that occurred.
+    pub fn error(&self) -> &io::Error {
+        &self.error
+    }
+
+    /// Returns the element that was removed, or `None` if the removal
+    /// failed to be logged and the list is unchanged.
+    pub fn into_removed(self) -> Option<T> {
+        self.removed
+    }
+}
+
+impl<T> fmt::Debug for RemoveError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RemoveError")
+            .field("error", &self.error)
+            .field("removed", &self.removed.is_some())
+            .finish_non_exhaustive()
+    }
+}
+
+impl<T> fmt::Display for RemoveError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.removed {
+            Some(_) => write!(
+                f,
+                "element removed, but syncing or compacting the log failed: {}",
+                self.error
+            ),
+            None => write!(f, "failed to log removal: {}", self.error),
+        }
+    }
+}
+
+impl<T> Error for RemoveError<T> {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        Some(&self.error)
+    }
+}
+
+impl<T> From<RemoveError<T>> for io::Error {
+    fn from(err: RemoveError<T>) -> Self {
+        err.error
+    }
+}
+
+/// A `DoublyLinkedList` whose mutations are recorded in a write-ahead log,
+/// so it can be recovered after a crash.
+///
+/// Mutations return an error if they fail to be logged, in which case the
+/// list is unchanged, or if they are logged and applied but syncing or
+/// compacting the log afterwards fails. In the latter case the mutation
+/// stands, and removals hand back the removed element in their
+/// [`RemoveError`]. Only one `DurableList` may use a directory at a time.
+///
+/// ```
+/// use dll_rs::durable::{DurableList, SyncPolicy};
+///
+/// let dir = std::env::temp_dir().join(format!("dll-rs-doc-{}", std::process::id()));
+/// # let _ = std::fs::remove_dir_all(&dir);
+/// let mut jobs = DurableList::open(&dir, SyncPolicy::Always)?;
+/// jobs.push_back("resize".to_string())?;
+/// jobs.push_back("upload".to_string())?;
+/// assert_eq!(jobs.pop_front()?.as_deref(), Some("resize"));
+/// drop(jobs);
+///
+/// // Opening the directory again replays the log
+/// let jobs = DurableList::<String>::open(&dir, SyncPolicy::Always)?;
+/// assert_eq!(jobs.iter().collect::<Vec<_>>(), ["upload"]);
+/// # std::fs::remove_dir_all(&dir)?;
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+pub struct DurableList<T> {
+    list: DoublyLinkedList<T>,
+    dir: PathBuf,
+    generation: u64,
+    log: File,
+    log_len: u64,
+    sync: SyncPolicy,
+    unsynced: usize, // Mutations logged since the last sync
+    compaction_threshold: u64,
+    retired: Option<u64>, // The previous generation, until its files are gone
+    buf: Vec<u8>,         // Reused to encode records
+}
+
+impl<T: Codec> DurableList<T> {
+    /// Opens the durable list in `dir`, creating the directory if needed,
+    /// and recovers the list from its snapshot and log. The log is
+    /// compacted once it grows past [`DEFAULT_COMPACTION_THRESHOLD`].
+    ///
+    /// A record cut short by a crash at the end of the log is dropped, along
+    /// with the mutation it recorded.
+    ///
+    /// # Errors
+    /// Fails if the directory cannot be read or written, or if its snapshot
+    /// or log are corrupted beyond a torn last record. A record with a bad
+    /// checksum followed by more is reported as
+    /// [`OpenError::CorruptRecord`], leaving the log untouched.
+    pub fn open(dir: impl AsRef<Path>, sync: SyncPolicy) -> Result<Self, OpenError> {
+        Self::with_compaction_threshold(dir, sync, DEFAULT_COMPACTION_THRESHOLD)
+    }
+
+    /// Opens the durable list in `dir` like [`DurableList::open`], compacting
+    /// the log once it grows past `threshold` bytes.
+    pub fn with_compaction_threshold(
+        dir: impl AsRef<Path>,
+        sync: SyncPolicy,
+        threshold: u64,
+    ) -> Result<Self, OpenError> {
+        let dir = dir.as_ref().to_path_buf();
+        fs::create_dir_all(&dir)?;
+        // The latest snapshot decides the generation, and every other file
+        // is left over from a crash in the middle of a compaction
+        let (mut snapshots, mut logs) = (Vec::new(), Vec::new());
+        for entry in fs::read_dir(&dir)? {
+            let name = entry?.file_name();
+            let Some(name) = name.to_str() else {
+                continue;
+            };
+            if let Some(generation) = name.strip_prefix("snapshot.") {
+                snapshots.extend(generation.parse::<u64>());
+            } else if let Some(generation) = name.strip_prefix("wal.") {
+                logs.extend(generation.parse::<u64>());
+            }
+        }
+        let generation = snapshots.iter().copied().max().unwrap_or(0);
+        let mut list = match File::open(dir.join(format!("snapshot.{generation}"))) {
+            Ok(file) => DoublyLinkedList::read_snapshot(BufReader::new(file))?,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => DoublyLinkedList::new(),
+            Err(err) => return Err(err.into()),
+        };
+        let mut log = OpenOptions::new()
+            .read(true)
+            .append(true)
+            .create(true)
+            .open(dir.join(format!("wal.{generation}")))?;
+        let log_len = replay(&mut list, &mut log)?;
+        for old in snapshots.into_iter().filter(|&g| g != generation) {
+            remove_if_exists(&dir.join(format!("snapshot.{old}")))?;
+        }
+        // Including logs of a next generation whose snapshot never made it
+        for old in logs.into_iter().filter(|&g| g != generation) {
+            remove_if_exists(&dir.join(format!("wal.{old}")))?;
+        }
+        remove_if_exists(&dir.join("snapshot.tmp"))?;
+        Ok(DurableList {
+            list,
+            dir,
+            generation,
+            log,
+            log_len,
+            sync,
+            unsynced: 0,
+            compaction_threshold: threshold,
+            retired: None,
+            buf: Vec::new(),
+        })
+    }
+
+    /// Adds an element to the front of the list.
+    pub fn push_front(&mut self, val: T) -> io::Result<()> {
+        self.log(PUSH_FRONT, None, Some(&val))?;
+        self.list.push_front(val);
+        self.after_mutation()
+    }
+
+    /// Adds an element to the back of the list.
+    pub fn push_back(&mut self, val: T) -> io::Result<()> {
+        self.log(PUSH_BACK, None, Some(&val))?;
+        self.list.push_back(val);
+        self.after_mutation()
+    }
+
+    /// Removes the first element and returns it, or `None` if the list is
+    /// empty.
+    pub fn pop_front(&mut self) -> Result<Option<T>, RemoveError<T>> {
+        if self.list.is_empty() {
+            return Ok(None);
+        }
+        self.log_removal(POP_FRONT, None)?;
+        let val = self.list.pop_front().unwrap();
+        self.after_removal(val).map(Some)
+    }
+
+    /// Removes the last element and returns it, or `None` if the list is
+    /// empty.
+    pub fn pop_back(&mut self) -> Result<Option<T>, RemoveError<T>> {
+        if self.list.is_empty() {
+            return Ok(None);
+        }
+        self.log_removal(POP_BACK, None)?;
+        let val = self.list.pop_back().unwrap();
+        self.after_removal(val).map(Some)
+    }
+
+    /// Inserts `val` at `index`, shifting everything after it towards the
+    /// back.
+    ///
+    /// # Panics
+    /// Panics if `index > len`.
+    pub fn insert(&mut self, index: usize, val: T) -> io::Result<()> {
+        let len = self.list.len();
+        assert!(
+            index <= len,
+            "index {index} is out of bounds for a list of length {len}"
+        );
+        self.log(INSERT, Some(index), Some(&val))?;
+        self.list.insert(index, val);
+        self.after_mutation()
+    }
+
+    /// Removes the element at `index` and returns it.
+    ///
+    /// # Panics
+    /// Panics if `index >= len`.
+    pub fn remove(&mut self, index: usize) -> Result<T, RemoveError<T>> {
+        let len = self.list.len();
+        assert!(
+            index < len,
+            "index {index} is out of bounds for a list of length {len}"
+        );
+        self.log_removal(REMOVE, Some(index))?;
+        let val = self.list.remove(index);
+        self.after_removal(val)
+    }
+
+    /// Removes all elements.
+    pub fn clear(&mut self) -> io::Result<()> {
+        self.log(CLEAR, None, None)?;
+        self.list.clear();
+        self.after_mutation()
+    }
+
+    /// Flushes every logged mutation to disk, and finishes a compaction
+    /// that failed after switching generations.
+    pub fn sync(&mut self) -> io::Result<()> {
+        self.log.sync_data()?;
+        self.unsynced = 0;
+        self.retire()
+    }
+
+    /// Writes the list to a snapshot of the next generation and starts an
+    /// empty log on top of it, then removes the files of the previous
+    /// generation.
+    ///
+    /// Renaming the snapshot into place is the point of no return: if a
+    /// step before fails, the list stays on the current generation, and if
+    /// one after fails, it has switched to the next one anyway. The files of
+    /// the previous generation are then removed by the next [`sync`] or
+    /// compaction instead.
+    ///
+    /// [`sync`]: DurableList::sync
+    pub fn compact(&mut self) -> io::Result<()> {
+        // Left over from a failed compaction, and about to be overwritten
+        self.retire()?;
+        let next = self.generation + 1;
+        let tmp = self.dir.join("snapshot.tmp");
+        let mut snapshot = BufWriter::new(File::create(&tmp)?);
+        self.list.write_snapshot(&mut snapshot)?;
+        snapshot.into_inner()?.sync_all()?;
+        // The log must exist and be empty before the snapshot it belongs to
+        let log = OpenOptions::new()
+            .read(true)
+            .append(true)
+            .create(true)
+            .truncate(false)
+            .open(self.dir.join(format!("wal.{next}")))?;
+        log.set_len(0)?;
+        log.sync_all()?;
+        fs::rename(&tmp, self.dir.join(format!("snapshot.{next}")))?;
+
+        self.retired = Some(self.generation);
+        self.generation = next;
+        self.log = log;
+        self.log_len = 0;
+        self.unsynced = 0;
+        self.retire()
+    }
+
+    /// Makes the switch away from the previous generation durable and
+    /// removes its files, if a compaction has not done so yet.
+    fn retire(&mut self) -> io::Result<()> {
+        let Some(old) = self.retired else {
+            return Ok(());
+        };
+        sync_dir(&self.dir)?;
+        remove_if_exists(&self.dir.join(format!("snapshot.{old}")))?;
+        remove_if_exists(&self.dir.join(format!("wal.{old}")))?;
+        self.retired = None;
+        Ok(())
+    }
+
+    /// Appends a record for operation `op` to the log.
+    fn log(&mut self, op: u8, index: Option<usize>, val: Option<&T>) -> io::Result<()> {
+        let buf = &mut self.buf;
+        buf.clear();
+        buf.extend_from_slice(&[0; HEADER_LEN]);
+        buf.push(op);
+        if let Some(index) = index {
+            buf.extend_from_slice(&(index as u64).to_le_bytes());
+        }
+        if let Some(val) = val {
+            val.encode(buf);
+        }
+        let payload = &buf[HEADER_LEN..];
+        let len = u32::try_from(payload.len()).map_err(|_| {
+            io::Error::new(io::ErrorKind::InvalidInput, "element too large for the log")
+        })?;
+        let header = header(len, payload);
+        buf[..HEADER_LEN].copy_from_slice(&header);
+        if let Err(err) = self.log.write_all(buf) {
+            // Drop whatever part of the record made it, so later records
+            // are not appended behind a torn one
+            self.log.set_len(self.log_len)?;
+            return Err(err);
+        }
+        self.log_len += buf.len() as u64;
+        Ok(())
+    }
+
+    /// Appends a record for removal `op` to the log.
+    fn log_removal(&mut self, op: u8, index: Option<usize>) -> Result<(), RemoveError<T>> {
+        self.log(op, index, None).map_err(|error| RemoveError {
+            error,
+            removed: None,
+        })
+    }
+
+    /// Like `after_mutation`, but hands back `val`, the element removed,
+    /// even if that fails.
+    fn after_removal(&mut self, val: T) -> Result<T, RemoveError<T>> {
+        match self.after_mutation() {
+            Ok(()) => Ok(val),
+            Err(error) => Err(RemoveError {
+                error,
+                removed: Some(val),
+            }),
+        }
+    }
+
+    /// Syncs and compacts the log as configured, once a mutation is logged
+    /// and applied.
+    fn after_mutation(&mut self) -> io::Result<()> {
+        self.unsynced += 1;
+        if self.log_len >= self.compaction_threshold {
+            return self.compact();
+        }
+        match self.sync {
+            SyncPolicy::Always => self.sync(),
+            SyncPolicy::EveryN(n) if self.unsynced >= n => self.sync(),
+            SyncPolicy::EveryN(_) | SyncPolicy::Manual => Ok(()),
+        }
+    }
+}
+
+impl<T> DurableList<T> {
+    /// Returns the number of elements in the list.
+    pub fn len(&self) -> usize {
+        self.list.len()
+    }
+
+    /// Returns true if the list contains no elements.
+    pub fn is_empty(&self) -> bool {
+        self.list.is_empty()
+    }
+
+    /// Returns an iterator over the elements, front to back.
+    pub fn iter(&self) -> Iter<'_, T> {
+        self.list.iter()
+    }
+
+    /// Returns the recovered list.
+    pub fn as_list(&self) -> &DoublyLinkedList<T> {
+        &self.list
+    }
+
+    /// Returns the directory the list is stored in.
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for DurableList<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DurableList")
+            .field("list", &self.list)
+            .field("dir", &self.dir)
+            .field("generation", &self.generation)
+            .field("sync", &self.sync)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Returns the header of a record holding `payload`, of length `len`.
+fn header(len: u32, payload: &[u8]) -> [u8; HEADER_LEN] {
+    let crc = |bytes: &[u8]| {
+        let mut crc = Crc32::new();
+        crc.update(bytes);
+        crc.finish().to_le_bytes()
+    };
+    let len = len.to_le_bytes();
+    let mut header = [0; HEADER_LEN];
+    header[..4].copy_from_slice(&len);
+    header[4..8].copy_from_slice(&crc(&len));
+    header[8..].copy_from_slice(&crc(payload));
+    header
+}
+
+/// Applies every record of `log` to `list` and returns the length of the
+/// log. A torn record at the end is cut off, while a corrupted record
+/// anywhere before is an error.
+fn replay<T: Codec>(list: &mut DoublyLinkedList<T>, log: &mut File) -> Result<u64, OpenError> {
+    let mut reader = BufReader::new(&mut *log);
+    let mut valid_len = 0;
+    let mut payload = Vec::new();
+    // A record running up to the end of the log may have been torn by a
+    // crash, whatever the sync policy. One followed by more data may not
+    for index in 0.. {
+        let mut header = [0; HEADER_LEN];
+        if !read_full(&mut reader, &mut header)? {
+            break;
+        }
+        let len = u32::from_le_bytes(header[..4].try_into().unwrap());
+        if header[4..8] != self::header(len, &[])[4..8] {
+            if reader.fill_buf()?.is_empty() {
+                break;
+            }
+            return Err(OpenError::CorruptRecord { index });
+        }
+        payload.clear();
+        (&mut reader).take(len.into()).read_to_end(&mut payload)?;
+        if payload.len() != len as usize {
+            break;
+        }
+        if header != self::header(len, &payload) {
+            if reader.fill_buf()?.is_empty() {
+                break;
+            }
+            return Err(OpenError::CorruptRecord { index });
+        }
+        apply(list, &payload).ok_or(OpenError::InvalidRecord { index })?;
+        valid_len += (HEADER_LEN + payload.len()) as u64;
+    }
+    drop(reader);
+    if log.seek(SeekFrom::End(0))? != valid_len {
+        log.set_len(valid_len)?;
+        log.sync_all()?;
+    }
+    Ok(valid_len)
+}
+
+/// Applies the mutation recorded in `payload` to `list`.
+fn apply<T: Codec>(list: &mut DoublyLinkedList<T>, payload: &[u8]) -> Option<()> {
+    let (&op, args) = payload.split_first()?;
+    let decode = |bytes| T::decode(bytes).ok();
+    let index = || {
+        let (index, rest) = args.split_first_chunk::<8>()?;
+        Some((usize::try_from(u64::from_le_bytes(*index)).ok()?, rest))
+    };
+    match op {
+        PUSH_FRONT => list.push_front(decode(args)?),
+        PUSH_BACK => list.push_back(decode(args)?),
+        POP_FRONT if args.is_empty() => drop(list.pop_front()?),
+        POP_BACK if args.is_empty() => drop(list.pop_back()?),
+        INSERT => {
+            let (index, val) = index()?;
+            list.try_insert(index, decode(val)?).ok()?;
+        }
+        REMOVE => match index()? {
+            (index, []) => drop(list.try_remove(index).ok()?),
+            _ => return None,
+        },
+        CLEAR if args.is_empty() => list.clear(),
+        _ => return None,
+    }
+    Some(())
+}
+
+/// Fills `buf` from `r`. Returns false if `r` ends first, whether before
+/// the first byte or after it, as it does within a torn header.
+fn read_full(r: &mut impl Read, buf: &mut [u8]) -> io::Result<bool> {
+    let mut filled = 0;
+    while filled < buf.len() {
+        match r.read(&mut buf[filled..]) {
+            Ok(0) => return Ok(false),
+            Ok(n) => filled += n,
+            Err(err) if err.kind() == io::ErrorKind::Interrupted => {}
+            Err(err) => return Err(err),
+        }
+    }
+    Ok(true)
+}
+
+fn remove_if_exists(path: &Path) -> io::Result<()> {
+    match fs::remove_file(path) {
+        Err(err) if err.kind() != io::ErrorKind::NotFound => Err(err),
+        _ => Ok(()),
+    }
+}
+
+/// Makes renames and new files in `dir` durable.
+fn sync_dir(dir: &Path) -> io::Result<()> {
+    // Directories cannot be opened as files everywhere, and only need to be
+    // synced on Unix
+    if cfg!(unix) {
+        File::open(dir)?.sync_all()?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// A fresh directory under the system's temporary directory, removed
+    /// again on drop.
+    struct TempDir(PathBuf);
+
+    impl TempDir {
+        fn new() -> Self {
+            static NEXT: AtomicUsize = AtomicUsize::new(0);
+            let name = format!(
+                "dll-rs-durable-{}-{}",
+                std::process::id(),
+                NEXT.fetch_add(1, Ordering::Relaxed)
+            );
+            let dir = std::env::temp_dir().join(name);
+            let _ = fs::remove_dir_all(&dir);
+            TempDir(dir)
+        }
+
+        fn files(&self) -> Vec<String> {
+            let mut files: Vec<_> = fs::read_dir(&self.0)
+                .unwrap()
+                .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+                .collect();
+            files.sort();
+            files
+        }
+    }
+
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    fn contents(list: &DurableList<u32>) -> Vec<u32> {
+        list.iter().copied().collect()
+    }
+
+    #[test]
+    fn test_reopen_replays_every_operation() {
+        let dir = TempDir::new();
+        let mut list = DurableList::open(&dir.0, SyncPolicy::EveryN(3)).unwrap();
+        for i in 0..5 {
+            list.push_back(i).unwrap();
+        }
+        list.push_front(10).unwrap();
+        assert_eq!(list.pop_back().unwrap(), Some(4));
+        assert_eq!(list.pop_front().unwrap(), Some(10));
+        list.insert(2, 20).unwrap();
+        assert_eq!(list.remove(0).unwrap(), 0);
+        assert_eq!(contents(&list), [1, 20, 2, 3]);
+        drop(list);
+
+        let mut list = DurableList::<u32>::open(&dir.0, SyncPolicy::Manual).unwrap();
+        assert_eq!(contents(&list), [1, 20, 2, 3]);
+        list.clear().unwrap();
+        list.push_back(7).unwrap();
+        list.sync().unwrap();
+        drop(list);
+        let list = DurableList::<u32>::open(&dir.0, SyncPolicy::Manual).unwrap();
+        assert_eq!(contents(&list), [7]);
+        assert_eq!(dir.files(), ["wal.0"]);
+    }
+
+    #[test]
+    fn test_torn_record_is_dropped() {
+        let dir = TempDir::new();
+        let mut list = DurableList::open(&dir.0, SyncPolicy::Always).unwrap();
+        list.push_back(1).unwrap();
+        list.push_back(2).unwrap();
+        drop(list);
+
+        // Cut the last record short, as a crash in the middle of a write
+        let wal = dir.0.join("wal.0");
+        let len = fs::metadata(&wal).unwrap().len();
+        OpenOptions::new()
+            .write(true)
+            .open(&wal)
+            .unwrap()
+            .set_len(len - 2)
+            .unwrap();
+        let mut list = DurableList::<u32>::open(&dir.0, SyncPolicy::Always).unwrap();
+        assert_eq!(contents(&list), [1]);
+        list.push_back(3).unwrap();
+        drop(list);
+        let list = DurableList::<u32>::open(&dir.0, SyncPolicy::Always).unwrap();
+        assert_eq!(contents(&list), [1, 3]);
+
+        // Cut the log within the header of a record instead
+        let len = fs::metadata(&wal).unwrap().len();
+        let mut log = OpenOptions::new().append(true).open(&wal).unwrap();
+        log.write_all(&[5, 0, 0]).unwrap();
+        drop(log);
+        let mut list = DurableList::<u32>::open(&dir.0, SyncPolicy::Always).unwrap();
+        assert_eq!(contents(&list), [1, 3]);
+        assert_eq!(fs::metadata(&wal).unwrap().len(), len);
+        list.push_back(4).unwrap();
+        drop(list);
+        let list = DurableList::<u32>::open(&dir.0, SyncPolicy::Always).unwrap();
+        assert_eq!(contents(&list), [1, 3, 4]);
+
+        // A checksum that matches but a record that cannot be applied is
+        // not a torn write
+        let mut log = OpenOptions::new().append(true).open(&wal).unwrap();
+        log.write_all(&header(2, &[POP_FRONT, 0])).unwrap();
+        log.write_all(&[POP_FRONT, 0]).unwrap();
+        let err = DurableList::<u32>::open(&dir.0, SyncPolicy::Always).unwrap_err();
+        assert!(matches!(err, OpenError::InvalidRecord { index: 3 }));
+    }
+
+    #[test]
+    fn test_log_is_compacted_into_snapshots() {
+        let dir = TempDir::new();
+        let mut list =
+            DurableList::with_compaction_threshold(&dir.0, SyncPolicy::Manual, 100).unwrap();
+        for i in 0..50 {
+            list.push_back(i).unwrap();
+            if i % 3 == 0 {
+                list.pop_front().unwrap();
+            }
+        }
+        assert!(list.generation > 1);
+        assert!(list.log_len < 100);
+        let expected = contents(&list);
+        let generation = list.generation;
+        drop(list);
+        assert_eq!(
+            dir.files(),
+            [
+                format!("snapshot.{generation}"),
+                format!("wal.{generation}")
+            ]
+        );
+
+        // A crash right after the next snapshot is written leaves the old
+        // generation behind, which is cleaned up on open. With nothing
+        // logged since, the next snapshot is a copy of the last one
+        let mut list = DurableList::<u32>::open(&dir.0, SyncPolicy::Manual).unwrap();
+        assert_eq!(contents(&list), expected);
+        list.compact().unwrap();
+        let generation = generation + 1;
+        drop(list);
+        fs::copy(
+            dir.0.join(format!("snapshot.{generation}")),
+            dir.0.join(format!("snapshot.{}", generation + 1)),
+        )
+        .unwrap();
+        let list = DurableList::<u32>::open(&dir.0, SyncPolicy::Manual).unwrap();
+        assert_eq!(contents(&list), expected);
+        assert_eq!(
+            dir.files(),
+            [
+                format!("snapshot.{}", generation + 1),
+                format!("wal.{}", generation + 1)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_failure_after_switching_generations() {
+        let dir = TempDir::new();
+        let mut list = DurableList::open(&dir.0, SyncPolicy::Manual).unwrap();
+        list.push_back(1).unwrap();
+
+        // A directory in place of the next log fails the compaction before
+        // the new snapshot is in place, so the list stays where it was
+        fs::create_dir(dir.0.join("wal.1")).unwrap();
+        assert!(list.compact().is_err());
+        assert!(!dir.0.join("snapshot.1").exists());
+        list.push_back(2).unwrap();
+        list.sync().unwrap();
+        assert_eq!(list.generation, 0);
+        fs::remove_dir(dir.0.join("wal.1")).unwrap();
+
+        // A directory in place of the old snapshot cannot be removed, which
+        // fails the compaction after the new snapshot is in place
+        fs::create_dir(dir.0.join("snapshot.0")).unwrap();
+        assert!(list.compact().is_err());
+        list.push_back(3).unwrap();
+        assert!(list.sync().is_err());
+        assert_eq!(list.generation, 1);
+
+        fs::remove_dir(dir.0.join("snapshot.0")).unwrap();
+        list.sync().unwrap();
+        assert_eq!(dir.files(), ["snapshot.1", "wal.1"]);
+        drop(list);
+        let list = DurableList::<u32>::open(&dir.0, SyncPolicy::Manual).unwrap();
+        assert_eq!(contents(&list), [1, 2, 3]);
+    }
+
+    #[test]
+    fn test_failed_compaction_returns_removed() {
+        let dir = TempDir::new();
+        let mut list =
+            DurableList::with_compaction_threshold(&dir.0, SyncPolicy::Manual, 1).unwrap();
+        list.push_back(1).unwrap();
+        list.push_back(2).unwrap();
+
+        // The snapshot cannot be created in place of a directory
+        fs::create_dir(dir.0.join("snapshot.tmp")).unwrap();
+        let err = list.pop_front().unwrap_err();
+        assert_eq!(err.into_removed(), Some(1));
+        let err = list.remove(0).unwrap_err();
+        assert_eq!(err.into_removed(), Some(2));
+        assert!(list.is_empty());
+
+        fs::remove_dir(dir.0.join("snapshot.tmp")).unwrap();
+        list.push_back(3).unwrap();
+        drop(list);
+        let list = DurableList::<u32>::open(&dir.0, SyncPolicy::Manual).unwrap();
+        assert_eq!(contents(&list), [3]);
+    }
+
+    #[test]
+    fn test_corrupt_record_is_an_error() {
+        let dir = TempDir::new();
+        let mut list = DurableList::open(&dir.0, SyncPolicy::Manual).unwrap();
+        for i in 0..3 {
+            list.push_back(i).unwrap();
+        }
+        drop(list);
+
+        // Flip a bit in the payload of the second record. Each record is a
+        // header, the operation and 4 bytes of element
+        const RECORD: usize = HEADER_LEN + 5;
+        let wal = dir.0.join("wal.0");
+        let mut bytes = fs::read(&wal).unwrap();
+        bytes[RECORD + HEADER_LEN + 1] ^= 1;
+        fs::write(&wal, &bytes).unwrap();
+        let err = DurableList::<u32>::open(&dir.0, SyncPolicy::Manual).unwrap_err();
+        assert!(matches!(err, OpenError::CorruptRecord { index: 1 }));
+        assert_eq!(fs::read(&wal).unwrap(), bytes);
+        bytes[RECORD + HEADER_LEN + 1] ^= 1;
+
+        // A length grown past the end of the log is no torn write either
+        bytes[RECORD + 3] ^= 0x80;
+        fs::write(&wal, &bytes).unwrap();
+        let err = DurableList::<u32>::open(&dir.0, SyncPolicy::Manual).unwrap_err();
+        assert!(matches!(err, OpenError::CorruptRecord { index: 1 }));
+        assert_eq!(fs::read(&wal).unwrap(), bytes);
+        bytes[RECORD + 3] ^= 0x80;
+
+        // The same damage to the last record is taken for a torn write
+        bytes[2 * RECORD + HEADER_LEN + 1] ^= 1;
+        fs::write(&wal, &bytes).unwrap();
+        let list = DurableList::<u32>::open(&dir.0, SyncPolicy::Manual).unwrap();
+        assert_eq!(contents(&list), [0, 1]);
+    }
+
+    #[test]
+    fn test_open_removes_every_old_log() {
+        let dir = TempDir::new();
+        let mut list = DurableList::open(&dir.0, SyncPolicy::Manual).unwrap();
+        list.push_back(1).unwrap();
+
+        // As if the first compaction crashed before removing `wal.0`, and a
+        // later one before renaming its snapshot into place
+        let wal = fs::read(dir.0.join("wal.0")).unwrap();
+        list.compact().unwrap();
+        drop(list);
+        fs::write(dir.0.join("wal.0"), wal).unwrap();
+        fs::write(dir.0.join("wal.2"), []).unwrap();
+
+        let list = DurableList::<u32>::open(&dir.0, SyncPolicy::Manual).unwrap();
+        assert_eq!(contents(&list), [1]);
+        assert_eq!(dir.files(), ["snapshot.1", "wal.1"]);
+    }
+}
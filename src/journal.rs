@@ -0,0 +1,512 @@
+//! A `DoublyLinkedList` whose mutations can be undone and redone.
+
+use std::collections::VecDeque;
+use std::fmt;
+use std::mem;
+
+use crate::{DoublyLinkedList, Iter};
+
+/// The number of steps a [`JournaledList::new`] can undo.
+pub const DEFAULT_HISTORY_DEPTH: usize = 100;
+
+/// A mutation of the list, recorded as the one that reverts it.
+enum Op<T> {
+    Insert { index: usize, val: T },
+    Remove { index: usize },
+    Move { from: usize, to: usize },
+    Replace(DoublyLinkedList<T>),
+}
+
+impl<T> Op<T> {
+    /// Applies the operation to `list` and returns the one that reverts it.
+    fn apply(self, list: &mut DoublyLinkedList<T>) -> Self {
+        match self {
+            Op::Insert { index, val } => {
+                list.insert(index, val);
+                Op::Remove { index }
+            }
+            Op::Remove { index } => Op::Insert {
+                index,
+                val: list.remove(index),
+            },
+            Op::Move { from, to } => {
+                let val = list.remove(from);
+                list.insert(to, val);
+                Op::Move { from: to, to: from }
+            }
+            Op::Replace(other) => Op::Replace(mem::replace(list, other)),
+        }
+    }
+}
+
+/// Reverts a step by applying its operations last to first, and returns
+/// the step that reverts it in turn.
+fn revert<T>(list: &mut DoublyLinkedList<T>, step: Vec<Op<T>>) -> Vec<Op<T>> {
+    step.into_iter().rev().map(|op| op.apply(list)).collect()
+}
+
+/// A `DoublyLinkedList` that journals its mutations, so they can be undone
+/// and redone like the edits of a document.
+///
+/// Every mutation is one step of the history, unless it is made inside a
+/// transaction: the mutations between [`begin`] and [`commit`] are undone
+/// and redone together, or all reverted at once by [`rollback`].
+/// Transactions nest, and only the outermost one becomes a step. Making a
+/// new step discards the steps that could be redone, and only the latest
+/// [`history_depth`] steps are kept for undoing.
+///
+/// Elements can only be reached immutably, as changes made through
+/// references would escape the journal.
+///
+/// [`begin`]: JournaledList::begin
+/// [`commit`]: JournaledList::commit
+/// [`rollback`]: JournaledList::rollback
+/// [`history_depth`]: JournaledList::history_depth
+///
+/// ```
+/// use dll_rs::JournaledList;
+///
+/// let mut lines = JournaledList::new();
+/// lines.push_back("hello");
+/// lines.begin();
+/// lines.push_back("world");
+/// lines.move_to_front(1);
+/// lines.commit();
+/// assert_eq!(lines.iter().collect::<Vec<_>>(), [&"world", &"hello"]);
+///
+/// assert!(lines.undo());
+/// assert_eq!(lines.iter().collect::<Vec<_>>(), [&"hello"]);
+/// assert!(lines.redo());
+/// assert_eq!(lines.front(), Some(&"world"));
+/// ```
+pub struct JournaledList<T> {
+    list: DoublyLinkedList<T>,
+    undo: VecDeque<Vec<Op<T>>>, // Oldest step first
+    redo: Vec<Vec<Op<T>>>,      // Next step to redo last
+    open: Vec<Op<T>>,           // Recorded by the open transactions
+    marks: Vec<usize>,          // Where each open transaction starts in `open`
+    depth: usize,
+}
+
+impl<T> JournaledList<T> {
+    /// Creates an empty list that can undo up to [`DEFAULT_HISTORY_DEPTH`]
+    /// steps.
+    pub fn new() -> Self {
+        Self::with_history_depth(DEFAULT_HISTORY_DEPTH)
+    }
+
+    /// Creates an empty list that can undo up to `depth` steps.
+    pub fn with_history_depth(depth: usize) -> Self {
+        JournaledList {
+            list: DoublyLinkedList::new(),
+            undo: VecDeque::new(),
+            redo: Vec::new(),
+            open: Vec::new(),
+            marks: Vec::new(),
+            depth,
+        }
+    }
+
+    /// Returns the number of elements in the list.
+    pub fn len(&self) -> usize {
+        self.list.len()
+    }
+
+    /// Returns true if the list contains no elements.
+    pub fn is_empty(&self) -> bool {
+        self.list.is_empty()
+    }
+
+    /// Returns a reference to the first element, or `None` if the list is
+    /// empty.
+    pub fn front(&self) -> Option<&T> {
+        self.list.front()
+    }
+
+    /// Returns a reference to the last element, or `None` if the list is
+    /// empty.
+    pub fn back(&self) -> Option<&T> {
+        self.list.back()
+    }
+
+    /// Returns a reference to the element at `index`, or `None` if it is out
+    /// of bounds.
+    pub fn get(&self, index: usize) -> Option<&T> {
+        self.list.get(index)
+    }
+
+    /// Returns an iterator over the elements, front to back.
+    pub fn iter(&self) -> Iter<'_, T> {
+        self.list.iter()
+    }
+
+    /// Returns the current state of the list.
+    pub fn as_list(&self) -> &DoublyLinkedList<T> {
+        &self.list
+    }
+
+    /// Consumes the journal and returns the list, dropping its history.
+    pub fn into_list(self) -> DoublyLinkedList<T> {
+        self.list
+    }
+
+    /// Adds an element to the front of the list.
+    pub fn push_front(&mut self, val: T) {
+        self.list.push_front(val);
+        self.record(Op::Remove { index: 0 });
+    }
+
+    /// Adds an element to the back of the list.
+    pub fn push_back(&mut self, val: T) {
+        self.list.push_back(val);
+        let index = self.list.len() - 1;
+        self.record(Op::Remove { index });
+    }
+
+    /// Inserts `val` at `index`, shifting everything after it towards the
+    /// back.
+    ///
+    /// # Panics
+    /// Panics if `index > len`.
+    pub fn insert(&mut self, index: usize, val: T) {
+        self.list.insert(index, val);
+        self.record(Op::Remove { index });
+    }
+
+    /// Moves the element at `from` so that it ends up at `to`, shifting the
+    /// elements in between by one.
+    ///
+    /// # Panics
+    /// Panics if `from >= len` or `to >= len`.
+    pub fn move_to(&mut self, from: usize, to: usize) {
+        let len = self.list.len();
+        let index = from.max(to);
+        assert!(
+            index < len,
+            "index {index} is out of bounds for a list of length {len}"
+        );
+        if from != to {
+            let undo = Op::Move { from, to }.apply(&mut self.list);
+            self.record(undo);
+        }
+    }
+
+    /// Moves the element at `index` to the front of the list.
+    ///
+    /// # Panics
+    /// Panics if `index >= len`.
+    pub fn move_to_front(&mut self, index: usize) {
+        self.move_to(index, 0);
+    }
+
+    /// Moves the element at `index` to the back of the list.
+    ///
+    /// # Panics
+    /// Panics if `index >= len`.
+    pub fn move_to_back(&mut self, index: usize) {
+        self.move_to(index, self.list.len().saturating_sub(1).max(index));
+    }
+
+    /// Removes all elements. Undoing it brings them back without cloning
+    /// them.
+    pub fn clear(&mut self) {
+        if !self.list.is_empty() {
+            let old = mem::take(&mut self.list);
+            self.record(Op::Replace(old));
+        }
+    }
+
+    /// Starts a transaction, grouping the mutations made until the matching
+    /// [`commit`](JournaledList::commit) into one step.
+    pub fn begin(&mut self) {
+        self.marks.push(self.open.len());
+    }
+
+    /// Ends the innermost transaction. If it is the outermost one, its
+    /// mutations become a single step of the history.
+    ///
+    /// # Panics
+    /// Panics if no transaction is open.
+    pub fn commit(&mut self) {
+        assert!(self.marks.pop().is_some(), "no transaction to commit");
+        if self.marks.is_empty() && !self.open.is_empty() {
+            let step = mem::take(&mut self.open);
+            self.push_step(step);
+        }
+    }
+
+    /// Ends the innermost transaction by reverting every mutation made in
+    /// it. The history is left as it was when the transaction began.
+    ///
+    /// # Panics
+    /// Panics if no transaction is open.
+    pub fn rollback(&mut self) {
+        let mark = self.marks.pop().expect("no transaction to rollback");
+        let step = self.open.split_off(mark);
+        revert(&mut self.list, step);
+    }
+
+    /// Returns true if a transaction is open.
+    pub fn in_transaction(&self) -> bool {
+        !self.marks.is_empty()
+    }
+
+    /// Reverts the latest step, returning false if there is none.
+    ///
+    /// # Panics
+    /// Panics if a transaction is open.
+    pub fn undo(&mut self) -> bool {
+        assert!(self.marks.is_empty(), "cannot undo inside a transaction");
+        let Some(step) = self.undo.pop_back() else {
+            return false;
+        };
+        self.redo.push(revert(&mut self.list, step));
+        true
+    }
+
+    /// Makes the latest undone step again, returning false if there is none.
+    ///
+    /// # Panics
+    /// Panics if a transaction is open.
+    pub fn redo(&mut self) -> bool {
+        assert!(self.marks.is_empty(), "cannot redo inside a transaction");
+        let Some(step) = self.redo.pop() else {
+            return false;
+        };
+        let step = revert(&mut self.list, step);
+        self.undo.push_back(step);
+        true
+    }
+
+    /// Returns true if there is a step to undo.
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    /// Returns true if there is a step to redo.
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+
+    /// Returns the number of steps that can be undone at most.
+    pub fn history_depth(&self) -> usize {
+        self.depth
+    }
+
+    /// Sets the number of steps that can be undone, forgetting the oldest
+    /// ones beyond it.
+    pub fn set_history_depth(&mut self, depth: usize) {
+        self.depth = depth;
+        self.trim_history();
+    }
+
+    /// Forgets every step, so that nothing can be undone or redone.
+    /// Transactions still open can be rolled back.
+    pub fn clear_history(&mut self) {
+        self.undo.clear();
+        self.redo.clear();
+    }
+
+    /// Records `op`, which reverts the mutation just made.
+    fn record(&mut self, op: Op<T>) {
+        if self.marks.is_empty() {
+            self.push_step(vec![op]);
+        } else {
+            self.open.push(op);
+        }
+    }
+
+    fn push_step(&mut self, step: Vec<Op<T>>) {
+        self.redo.clear();
+        self.undo.push_back(step);
+        self.trim_history();
+    }
+
+    fn trim_history(&mut self) {
+        let excess = self.undo.len().saturating_sub(self.depth);
+        self.undo.drain(..excess);
+    }
+}
+
+impl<T: Clone> JournaledList<T> {
+    /// Removes the first element and returns it, or `None` if the list is
+    /// empty. The journal keeps a clone of it for undoing.
+    pub fn pop_front(&mut self) -> Option<T> {
+        let val = self.list.pop_front()?;
+        self.record(Op::Insert {
+            index: 0,
+            val: val.clone(),
+        });
+        Some(val)
+    }
+
+    /// Removes the last element and returns it, or `None` if the list is
+    /// empty. The journal keeps a clone of it for undoing.
+    pub fn pop_back(&mut self) -> Option<T> {
+        let val = self.list.pop_back()?;
+        let index = self.list.len();
+        self.record(Op::Insert {
+            index,
+            val: val.clone(),
+        });
+        Some(val)
+    }
+
+    /// Removes the element at `index` and returns it. The journal keeps a
+    /// clone of it for undoing.
+    ///
+    /// # Panics
+    /// Panics if `index >= len`.
+    pub fn remove(&mut self, index: usize) -> T {
+        let val = self.list.remove(index);
+        self.record(Op::Insert {
+            index,
+            val: val.clone(),
+        });
+        val
+    }
+}
+
+impl<T> Default for JournaledList<T> {
+    /// Creates an empty `JournaledList<T>`.
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> From<DoublyLinkedList<T>> for JournaledList<T> {
+    /// Starts journaling the mutations of `list`, with an empty history.
+    fn from(list: DoublyLinkedList<T>) -> Self {
+        JournaledList {
+            list,
+            ..Self::new()
+        }
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for JournaledList<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("JournaledList")
+            .field("list", &self.list)
+            .field("undo", &self.undo.len())
+            .field("redo", &self.redo.len())
+            .field("transactions", &self.marks.len())
+            .field("depth", &self.depth)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<'a, T> IntoIterator for &'a JournaledList<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn collect(list: &JournaledList<i32>) -> Vec<i32> {
+        list.iter().copied().collect()
+    }
+
+    #[test]
+    fn test_undo_redo_every_mutation() {
+        let mut list = JournaledList::from(DoublyLinkedList::from([1, 2, 3]));
+        let mut states = vec![collect(&list)];
+        let edits: [fn(&mut JournaledList<i32>); 9] = [
+            |l| l.push_front(0),
+            |l| l.push_back(4),
+            |l| assert_eq!(l.pop_front(), Some(0)),
+            |l| assert_eq!(l.pop_back(), Some(4)),
+            |l| l.insert(1, 10),
+            |l| assert_eq!(l.remove(2), 2),
+            |l| l.move_to(0, 2),
+            |l| l.move_to_back(0),
+            |l| l.clear(),
+        ];
+        for edit in edits {
+            edit(&mut list);
+            states.push(collect(&list));
+        }
+        assert_eq!(states[7..], [vec![10, 3, 1], vec![3, 1, 10], vec![]]);
+
+        for state in states.iter().rev().skip(1) {
+            assert!(list.undo());
+            assert_eq!(&collect(&list), state);
+        }
+        assert!(!list.undo());
+        for state in &states[1..] {
+            assert!(list.redo());
+            assert_eq!(&collect(&list), state);
+        }
+        assert!(!list.redo());
+
+        // Edits that change nothing are not steps
+        assert_eq!(list.pop_back(), None);
+        list.clear();
+        assert!(list.undo());
+        assert_eq!(collect(&list), [3, 1, 10]);
+    }
+
+    #[test]
+    fn test_transactions() {
+        let mut list = JournaledList::new();
+        list.push_back(1);
+        list.begin();
+        list.push_back(2);
+        list.begin();
+        list.push_front(0);
+        list.move_to_back(0);
+        list.rollback();
+        assert_eq!(collect(&list), [1, 2]);
+        list.begin();
+        list.push_back(3);
+        list.commit();
+        assert!(list.in_transaction());
+        list.commit();
+        assert_eq!(collect(&list), [1, 2, 3]);
+
+        assert!(list.undo());
+        assert_eq!(collect(&list), [1]);
+        assert!(list.redo());
+        assert_eq!(collect(&list), [1, 2, 3]);
+
+        // Rolling back the outermost transaction leaves the history alone
+        list.begin();
+        list.clear();
+        list.rollback();
+        assert!(list.undo() && list.undo());
+        assert!(list.is_empty());
+        assert!(list.can_redo());
+    }
+
+    #[test]
+    fn test_history_depth() {
+        let mut list = JournaledList::with_history_depth(3);
+        (0..5).for_each(|i| list.push_back(i));
+        while list.undo() {}
+        assert_eq!(collect(&list), [0, 1]);
+
+        // A new step discards what could be redone
+        list.push_front(9);
+        assert!(!list.can_redo());
+        list.set_history_depth(0);
+        assert!(!list.can_undo());
+        assert_eq!(
+            format!("{list:?}"),
+            "JournaledList { list: [9, 0, 1], undo: 0, redo: 0, transactions: 0, depth: 0, .. }"
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "cannot undo inside a transaction")]
+    fn test_undo_in_transaction_panics() {
+        let mut list = JournaledList::<i32>::new();
+        list.begin();
+        list.undo();
+    }
+}
@@ -0,0 +1,48 @@
+//! A doubly linked list implementation in Rust, maintaining borrow checking.
+//!
+//! ```
+//! use dll_rs::DoublyLinkedList;
+//!
+//! let mut list = DoublyLinkedList::new();
+//! list.push_back(2);
+//! list.push_front(1);
+//!
+//! assert_eq!(list.len(), 2);
+//! assert_eq!(list.pop_front(), Some(1));
+//! assert_eq!(list.pop_back(), Some(2));
+//! assert!(list.is_empty());
+//! ```
+
+pub mod arena;
+#[cfg(feature = "async")]
+pub mod async_deque;
+pub mod blocking;
+pub mod cache;
+pub mod concurrent;
+pub mod dll;
+pub mod durable;
+pub mod journal;
+pub mod lru;
+pub mod persistent;
+pub mod skip_list;
+pub mod snapshot;
+pub mod sorted;
+
+pub use dll::{
+    Cursor, CursorMut, DoublyLinkedList, Drain, ExtractIf, IndexOutOfBoundsError, IntoIter, Iter,
+    IterMut, NodeHandle, StaleHandleError,
+};
+
+pub use arena::{ArenaHandle, ArenaList};
+#[cfg(feature = "async")]
+pub use async_deque::{AsyncDeque, PopStream};
+pub use blocking::{BlockingDeque, PopError, PushError};
+pub use cache::{ArcCache, Cache, CacheStats, LfuCache, SlruCache, TwoQueueCache};
+pub use concurrent::SyncDoublyLinkedList;
+pub use durable::{DurableList, OpenError, RemoveError, SyncPolicy};
+pub use journal::JournaledList;
+pub use lru::LruCache;
+pub use persistent::PersistentList;
+pub use skip_list::SkipList;
+pub use snapshot::{Codec, DecodeError, SnapshotError};
+pub use sorted::SortedList;
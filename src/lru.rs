@@ -0,0 +1,392 @@
+use std::borrow::Borrow;
+use std::collections::HashMap;
+use std::fmt;
+use std::hash::Hash;
+use std::iter::FusedIterator;
+
+use crate::dll;
+use crate::{Cache, CacheStats, DoublyLinkedList, NodeHandle};
+
+// Called with every entry the cache evicts to stay within its capacity
+type EvictionCallback<K, V> = Box<dyn FnMut(K, V)>;
+
+/// A least recently used cache.
+///
+/// Entries are kept in a `DoublyLinkedList` ordered from most to least
+/// recently used, and a `HashMap` maps every key to the [`NodeHandle`] of
+/// its entry, so lookups, promotions and evictions are all O(1).
+///
+/// ```
+/// use dll_rs::LruCache;
+///
+/// let mut cache = LruCache::new(2);
+/// cache.put("a", 1);
+/// cache.put("b", 2);
+/// assert_eq!(cache.get("a"), Some(&1));
+///
+/// // "b" is now the least recently used entry, and makes room for "c"
+/// cache.put("c", 3);
+/// assert_eq!(cache.peek("b"), None);
+/// assert_eq!(cache.iter().collect::<Vec<_>>(), [(&"c", &3), (&"a", &1)]);
+/// ```
+pub struct LruCache<K, V> {
+    list: DoublyLinkedList<(K, V)>, // Most recently used first
+    map: HashMap<K, NodeHandle<(K, V)>>,
+    capacity: usize,
+    on_evict: Option<EvictionCallback<K, V>>,
+    stats: CacheStats,
+}
+
+impl<K: Hash + Eq + Clone, V> LruCache<K, V> {
+    /// Creates a new, empty cache holding at most `capacity` entries.
+    ///
+    /// # Panics
+    /// Panics if `capacity` is 0, as nothing could ever be cached.
+    pub fn new(capacity: usize) -> Self {
+        assert!(capacity > 0, "capacity of an LruCache must be positive");
+        LruCache {
+            list: DoublyLinkedList::new(),
+            map: HashMap::with_capacity(capacity),
+            capacity,
+            on_evict: None,
+            stats: CacheStats::default(),
+        }
+    }
+
+    /// Creates a new, empty cache holding at most `capacity` entries, which
+    /// hands every entry it evicts to `on_evict`.
+    ///
+    /// Only entries dropped to make room are passed to the callback, not
+    /// those taken out with [`LruCache::pop`], [`LruCache::pop_lru`] or
+    /// [`LruCache::clear`].
+    /// ```
+    /// use std::cell::RefCell;
+    /// use std::rc::Rc;
+    ///
+    /// use dll_rs::LruCache;
+    ///
+    /// let evicted = Rc::new(RefCell::new(Vec::new()));
+    /// let mut cache = LruCache::with_eviction_callback(1, {
+    ///     let evicted = Rc::clone(&evicted);
+    ///     move |key, val| evicted.borrow_mut().push((key, val))
+    /// });
+    /// cache.put(1, "one");
+    /// cache.put(2, "two");
+    /// assert_eq!(*evicted.borrow(), [(1, "one")]);
+    /// ```
+    ///
+    /// # Panics
+    /// Panics if `capacity` is 0, as nothing could ever be cached.
+    pub fn with_eviction_callback(capacity: usize, on_evict: impl FnMut(K, V) + 'static) -> Self {
+        let mut cache = Self::new(capacity);
+        cache.on_evict = Some(Box::new(on_evict));
+        cache
+    }
+
+    /// Returns true if the cache contains an entry for `key`, without
+    /// marking it as used.
+    pub fn contains<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.map.contains_key(key)
+    }
+
+    /// Returns a reference to the value of `key` and marks it as the most
+    /// recently used entry.
+    pub fn get<Q>(&mut self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.get_mut(key).map(|val| &*val)
+    }
+
+    /// Returns a mutable reference to the value of `key` and marks it as
+    /// the most recently used entry.
+    pub fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let handle = self.map.get(key);
+        self.stats.record_lookup(handle.is_some());
+        let handle = handle?;
+        self.list.move_to_front(handle).unwrap();
+        Some(&mut self.list.get_mut_by_handle(handle).unwrap().1)
+    }
+
+    /// Returns a reference to the value of `key` without marking it as used.
+    pub fn peek<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let handle = self.map.get(key)?;
+        Some(&self.list.get_by_handle(handle).unwrap().1)
+    }
+
+    /// Returns the least recently used entry, the next one to be evicted,
+    /// without marking it as used.
+    pub fn peek_lru(&self) -> Option<(&K, &V)> {
+        self.list.back().map(|(key, val)| (key, val))
+    }
+
+    /// Inserts a value for `key` and marks it as the most recently used
+    /// entry, evicting the least recently used one if the cache is full.
+    ///
+    /// Returns the previous value of `key`, if there was one.
+    pub fn put(&mut self, key: K, val: V) -> Option<V> {
+        if let Some(handle) = self.map.get(&key) {
+            self.list.move_to_front(handle).unwrap();
+            let entry = self.list.get_mut_by_handle(handle).unwrap();
+            return Some(std::mem::replace(&mut entry.1, val));
+        }
+        let handle = self.list.push_front_with_handle((key.clone(), val));
+        self.map.insert(key, handle);
+        self.evict_to(self.capacity);
+        None
+    }
+
+    /// Removes the entry for `key` and returns its value.
+    pub fn pop<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let handle = self.map.remove(key)?;
+        Some(self.list.remove_by_handle(&handle).unwrap().1)
+    }
+
+    /// Removes the least recently used entry and returns it.
+    pub fn pop_lru(&mut self) -> Option<(K, V)> {
+        let (key, val) = self.list.pop_back()?;
+        self.map.remove(&key);
+        Some((key, val))
+    }
+
+    /// Changes the capacity of the cache, evicting least recently used
+    /// entries until it fits.
+    ///
+    /// # Panics
+    /// Panics if `capacity` is 0, as nothing could ever be cached.
+    pub fn resize(&mut self, capacity: usize) {
+        assert!(capacity > 0, "capacity of an LruCache must be positive");
+        self.capacity = capacity;
+        self.evict_to(capacity);
+    }
+
+    /// Removes all entries, without passing them to the eviction callback.
+    pub fn clear(&mut self) {
+        self.map.clear();
+        self.list.clear();
+    }
+
+    /// Drops least recently used entries until at most `len` are left.
+    fn evict_to(&mut self, len: usize) {
+        while self.list.len() > len {
+            let (key, val) = self.pop_lru().unwrap();
+            self.stats.evictions += 1;
+            if let Some(on_evict) = &mut self.on_evict {
+                on_evict(key, val);
+            }
+        }
+    }
+}
+
+impl<K, V> LruCache<K, V> {
+    /// Returns the number of entries in the cache.
+    pub fn len(&self) -> usize {
+        self.list.len()
+    }
+
+    /// Returns true if the cache contains no entries.
+    pub fn is_empty(&self) -> bool {
+        self.list.is_empty()
+    }
+
+    /// Returns the maximum number of entries the cache holds.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Returns the hit, miss and eviction counters. Only lookups through
+    /// [`LruCache::get`] and [`LruCache::get_mut`] are counted.
+    pub fn stats(&self) -> CacheStats {
+        self.stats
+    }
+
+    /// Sets all counters back to 0.
+    pub fn reset_stats(&mut self) {
+        self.stats = CacheStats::default();
+    }
+
+    /// Returns an iterator over the entries, from the most to the least
+    /// recently used. Iterating does not mark entries as used.
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        Iter {
+            inner: self.list.iter(),
+        }
+    }
+}
+
+impl<K: Hash + Eq + Clone, V> Cache<K, V> for LruCache<K, V> {
+    fn get(&mut self, key: &K) -> Option<&V> {
+        LruCache::get(self, key)
+    }
+
+    fn peek(&self, key: &K) -> Option<&V> {
+        LruCache::peek(self, key)
+    }
+
+    fn contains(&self, key: &K) -> bool {
+        LruCache::contains(self, key)
+    }
+
+    fn put(&mut self, key: K, val: V) -> Option<V> {
+        LruCache::put(self, key, val)
+    }
+
+    fn pop(&mut self, key: &K) -> Option<V> {
+        LruCache::pop(self, key)
+    }
+
+    fn clear(&mut self) {
+        LruCache::clear(self)
+    }
+
+    fn len(&self) -> usize {
+        LruCache::len(self)
+    }
+
+    fn capacity(&self) -> usize {
+        LruCache::capacity(self)
+    }
+
+    fn stats(&self) -> CacheStats {
+        LruCache::stats(self)
+    }
+
+    fn reset_stats(&mut self) {
+        LruCache::reset_stats(self)
+    }
+}
+
+impl<K: fmt::Debug, V: fmt::Debug> fmt::Debug for LruCache<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+/// An iterator over the entries of an [`LruCache`], from the most to the
+/// least recently used.
+///
+/// This `struct` is created by [`LruCache::iter`]. See its documentation
+/// for more.
+#[derive(Clone)]
+pub struct Iter<'a, K, V> {
+    inner: dll::Iter<'a, (K, V)>,
+}
+
+impl<'a, K, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|(key, val)| (key, val))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<K, V> DoubleEndedIterator for Iter<'_, K, V> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.inner.next_back().map(|(key, val)| (key, val))
+    }
+}
+
+impl<K, V> ExactSizeIterator for Iter<'_, K, V> {}
+
+impl<K, V> FusedIterator for Iter<'_, K, V> {}
+
+impl<'a, K, V> IntoIterator for &'a LruCache<K, V> {
+    type Item = (&'a K, &'a V);
+    type IntoIter = Iter<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::LruCache;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    fn keys<K: Copy, V>(cache: &LruCache<K, V>) -> Vec<K> {
+        cache.iter().map(|(&key, _)| key).collect()
+    }
+
+    #[test]
+    fn test_get_promotes_and_put_evicts() {
+        let mut cache = LruCache::new(3);
+        assert_eq!(cache.put(1, "a"), None);
+        assert_eq!(cache.put(2, "b"), None);
+        assert_eq!(cache.put(3, "c"), None);
+        assert_eq!(keys(&cache), [3, 2, 1]);
+
+        assert_eq!(cache.get(&1), Some(&"a"));
+        assert_eq!(cache.peek(&2), Some(&"b"));
+        assert_eq!(keys(&cache), [1, 3, 2]);
+        assert_eq!(cache.peek_lru(), Some((&2, &"b")));
+
+        // Replacing a value promotes it without evicting anything
+        assert_eq!(cache.put(3, "C"), Some("c"));
+        assert_eq!(keys(&cache), [3, 1, 2]);
+
+        cache.put(4, "d");
+        assert!(!cache.contains(&2));
+        assert_eq!(cache.get(&2), None);
+        assert_eq!(keys(&cache), [4, 3, 1]);
+        assert_eq!(cache.iter().rev().len(), 3);
+
+        *cache.get_mut(&1).unwrap() = "A";
+        assert_eq!(format!("{cache:?}"), r#"{1: "A", 4: "d", 3: "C"}"#);
+    }
+
+    #[test]
+    fn test_pop_resize_and_eviction_callback() {
+        let evicted = Rc::new(RefCell::new(Vec::new()));
+        let mut cache = LruCache::with_eviction_callback(4, {
+            let evicted = Rc::clone(&evicted);
+            move |key: String, val| evicted.borrow_mut().push((key, val))
+        });
+        for (i, key) in ["a", "b", "c", "d"].into_iter().enumerate() {
+            cache.put(key.to_string(), i);
+        }
+
+        assert_eq!(cache.pop("b"), Some(1));
+        assert_eq!(cache.pop("b"), None);
+        assert_eq!(cache.pop_lru(), Some(("a".to_string(), 0)));
+        assert!(evicted.borrow().is_empty());
+
+        cache.put("e".to_string(), 4);
+        cache.resize(1);
+        assert_eq!(cache.capacity(), 1);
+        assert_eq!(
+            *evicted.borrow(),
+            [("c".to_string(), 2), ("d".to_string(), 3)]
+        );
+        cache.put("f".to_string(), 5);
+        assert_eq!(evicted.borrow().last(), Some(&("e".to_string(), 4)));
+
+        cache.clear();
+        assert!(cache.is_empty());
+        assert_eq!(evicted.borrow().len(), 3);
+        cache.put("g".to_string(), 6);
+        assert_eq!(cache.len(), 1);
+    }
+}
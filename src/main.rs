@@ -1,8 +1,6 @@
 // main.rs
 
-pub mod dll;
-
-use dll::DoublyLinkedList;
+use dll_rs::DoublyLinkedList;
 
 fn main() {
     let mut list = DoublyLinkedList::new();
@@ -22,4 +20,3 @@ fn main() {
 
     println!("Final list length: {}", list.len()); // Should be 0
 }
-
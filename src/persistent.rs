@@ -0,0 +1,627 @@
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::iter::FusedIterator;
+use std::sync::Arc;
+
+/// An element, or a node grouping two or three items of the level below.
+/// Items on the top level of a tree are always leaves, and every level of
+/// nesting holds nodes one level deeper.
+enum Item<T> {
+    Leaf(Arc<T>),
+    Node(Arc<Node<T>>),
+}
+
+struct Node<T> {
+    size: usize, // The number of elements below the node
+    items: Vec<Item<T>>,
+}
+
+/// A 2-3 finger tree: up to four items are kept at either end, and the
+/// items in between are grouped into nodes and pushed down into a tree one
+/// level deeper. Both ends are therefore always within reach, and pushing
+/// or popping onto the latest version only rarely reaches a level further
+/// down. The tree is strict rather than lazy, so an old version whose ends
+/// are full or empty pays for a walk down every time it is pushed or popped.
+enum Tree<T> {
+    Empty,
+    Single(Item<T>),
+    Deep(Arc<Deep<T>>),
+}
+
+struct Deep<T> {
+    size: usize,
+    front: Vec<Item<T>>, // 1 to 4 items
+    middle: Tree<T>,
+    back: Vec<Item<T>>, // 1 to 4 items
+}
+
+/// An immutable list whose versions share structure.
+///
+/// Pushing or popping returns a new version of the list and leaves the old
+/// one untouched, while both keep sharing all elements and most of their
+/// structure. Cloning and reading either end are O(1), and indexing takes
+/// O(log n).
+///
+/// Pushing or popping takes O(log n) in the worst case. It is amortized
+/// O(1) as long as every push or pop is made on the latest version, but not
+/// when the same old version is pushed or popped over and over.
+///
+/// The list is a finger tree of `Arc`s, so versions can be handed to other
+/// threads while this one moves on. Use a [`DoublyLinkedList`] instead when
+/// only the latest version is ever needed.
+///
+/// [`DoublyLinkedList`]: crate::DoublyLinkedList
+///
+/// ```
+/// use dll_rs::PersistentList;
+///
+/// let empty = PersistentList::new();
+/// let one = empty.push_back(1);
+/// let two = one.push_back(2).push_front(0);
+/// assert_eq!(two.iter().copied().collect::<Vec<_>>(), [0, 1, 2]);
+///
+/// // Older versions are still there
+/// assert_eq!(one.len(), 1);
+/// assert!(empty.is_empty());
+/// assert_eq!(two.pop_front().unwrap().front(), Some(&1));
+/// ```
+pub struct PersistentList<T> {
+    tree: Tree<T>,
+}
+
+impl<T> PersistentList<T> {
+    /// Creates a new, empty list.
+    pub fn new() -> Self {
+        PersistentList { tree: Tree::Empty }
+    }
+
+    /// Returns the number of elements in the list.
+    pub fn len(&self) -> usize {
+        self.tree.size()
+    }
+
+    /// Returns true if the list contains no elements.
+    pub fn is_empty(&self) -> bool {
+        matches!(self.tree, Tree::Empty)
+    }
+
+    /// Returns a reference to the first element, or `None` if the list is
+    /// empty.
+    pub fn front(&self) -> Option<&T> {
+        self.tree.front().map(Item::leaf)
+    }
+
+    /// Returns a reference to the last element, or `None` if the list is
+    /// empty.
+    pub fn back(&self) -> Option<&T> {
+        self.tree.back().map(Item::leaf)
+    }
+
+    /// Returns a reference to the element at `index`, or `None` if it is out
+    /// of bounds.
+    pub fn get(&self, index: usize) -> Option<&T> {
+        (index < self.len()).then(|| self.tree.get(index))
+    }
+
+    /// Returns a new version of the list with `val` added to the front.
+    pub fn push_front(&self, val: T) -> Self {
+        PersistentList {
+            tree: self.tree.push_front(Item::Leaf(Arc::new(val))),
+        }
+    }
+
+    /// Returns a new version of the list with `val` added to the back.
+    pub fn push_back(&self, val: T) -> Self {
+        PersistentList {
+            tree: self.tree.push_back(Item::Leaf(Arc::new(val))),
+        }
+    }
+
+    /// Returns a new version of the list without its first element, or
+    /// `None` if the list is empty.
+    pub fn pop_front(&self) -> Option<Self> {
+        let (_, tree) = self.tree.pop_front()?;
+        Some(PersistentList { tree })
+    }
+
+    /// Returns a new version of the list without its last element, or
+    /// `None` if the list is empty.
+    pub fn pop_back(&self) -> Option<Self> {
+        let (_, tree) = self.tree.pop_back()?;
+        Some(PersistentList { tree })
+    }
+
+    /// Returns true if `self` and `other` are the same version, or versions
+    /// sharing all of their structure. Takes O(1), and is a cheap way to
+    /// tell that two lists are equal before comparing them.
+    pub fn ptr_eq(&self, other: &Self) -> bool {
+        match (&self.tree, &other.tree) {
+            (Tree::Empty, Tree::Empty) => true,
+            (Tree::Single(Item::Leaf(a)), Tree::Single(Item::Leaf(b))) => Arc::ptr_eq(a, b),
+            (Tree::Deep(a), Tree::Deep(b)) => Arc::ptr_eq(a, b),
+            _ => false,
+        }
+    }
+
+    /// Returns an iterator over references to the elements, front to back.
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            front: vec![Pending::Tree(&self.tree)],
+            back: vec![Pending::Tree(&self.tree)],
+            len: self.len(),
+        }
+    }
+}
+
+impl<T> Item<T> {
+    fn size(&self) -> usize {
+        match self {
+            Item::Leaf(_) => 1,
+            Item::Node(node) => node.size,
+        }
+    }
+
+    /// Groups `items` into a node one level up.
+    fn node(items: Vec<Item<T>>) -> Self {
+        debug_assert!(matches!(items.len(), 2 | 3));
+        let size = items.iter().map(Item::size).sum();
+        Item::Node(Arc::new(Node { size, items }))
+    }
+
+    /// Returns the element of a top level item.
+    fn leaf(&self) -> &T {
+        match self {
+            Item::Leaf(val) => val,
+            Item::Node(_) => unreachable!("items on the top level are leaves"),
+        }
+    }
+
+    /// Returns the items grouped by an item from a middle tree.
+    fn children(&self) -> &[Item<T>] {
+        match self {
+            Item::Node(node) => &node.items,
+            Item::Leaf(_) => unreachable!("items of middle trees are nodes"),
+        }
+    }
+
+    /// Returns the element at `index` below the item, which must be less
+    /// than its size.
+    fn get(&self, mut index: usize) -> &T {
+        match self {
+            Item::Leaf(val) => val,
+            Item::Node(node) => {
+                for item in &node.items {
+                    if index < item.size() {
+                        return item.get(index);
+                    }
+                    index -= item.size();
+                }
+                unreachable!("index is within the node")
+            }
+        }
+    }
+}
+
+impl<T> Tree<T> {
+    fn size(&self) -> usize {
+        match self {
+            Tree::Empty => 0,
+            Tree::Single(item) => item.size(),
+            Tree::Deep(deep) => deep.size,
+        }
+    }
+
+    fn deep(front: Vec<Item<T>>, middle: Tree<T>, back: Vec<Item<T>>) -> Self {
+        let size = front.iter().chain(&back).map(Item::size).sum::<usize>() + middle.size();
+        Tree::Deep(Arc::new(Deep {
+            size,
+            front,
+            middle,
+            back,
+        }))
+    }
+
+    /// Builds a tree out of the 1 to 4 items of an end.
+    fn from_items(items: &[Item<T>]) -> Self {
+        match items {
+            [] => Tree::Empty,
+            [item] => Tree::Single(item.clone()),
+            _ => {
+                let (front, back) = items.split_at(items.len() / 2);
+                Tree::deep(front.to_vec(), Tree::Empty, back.to_vec())
+            }
+        }
+    }
+
+    fn front(&self) -> Option<&Item<T>> {
+        match self {
+            Tree::Empty => None,
+            Tree::Single(item) => Some(item),
+            Tree::Deep(deep) => deep.front.first(),
+        }
+    }
+
+    fn back(&self) -> Option<&Item<T>> {
+        match self {
+            Tree::Empty => None,
+            Tree::Single(item) => Some(item),
+            Tree::Deep(deep) => deep.back.last(),
+        }
+    }
+
+    /// Returns the element at `index`, which must be less than the size.
+    fn get(&self, mut index: usize) -> &T {
+        let deep = match self {
+            Tree::Single(item) => return item.get(index),
+            Tree::Deep(deep) => deep,
+            Tree::Empty => unreachable!("index is within the tree"),
+        };
+        for item in &deep.front {
+            if index < item.size() {
+                return item.get(index);
+            }
+            index -= item.size();
+        }
+        if index < deep.middle.size() {
+            return deep.middle.get(index);
+        }
+        index -= deep.middle.size();
+        for item in &deep.back {
+            if index < item.size() {
+                return item.get(index);
+            }
+            index -= item.size();
+        }
+        unreachable!("index is within the tree")
+    }
+
+    fn push_front(&self, item: Item<T>) -> Self {
+        match self {
+            Tree::Empty => Tree::Single(item),
+            Tree::Single(only) => Tree::deep(vec![item], Tree::Empty, vec![only.clone()]),
+            Tree::Deep(deep) => {
+                if let [a, b, c, d] = &deep.front[..] {
+                    // A full end keeps two items and pushes the rest down
+                    let node = Item::node(vec![b.clone(), c.clone(), d.clone()]);
+                    let middle = deep.middle.push_front(node);
+                    Tree::deep(vec![item, a.clone()], middle, deep.back.clone())
+                } else {
+                    let front = [item].into_iter().chain(deep.front.iter().cloned());
+                    Tree::deep(front.collect(), deep.middle.clone(), deep.back.clone())
+                }
+            }
+        }
+    }
+
+    fn push_back(&self, item: Item<T>) -> Self {
+        match self {
+            Tree::Empty => Tree::Single(item),
+            Tree::Single(only) => Tree::deep(vec![only.clone()], Tree::Empty, vec![item]),
+            Tree::Deep(deep) => {
+                if let [a, b, c, d] = &deep.back[..] {
+                    // See `push_front`
+                    let node = Item::node(vec![a.clone(), b.clone(), c.clone()]);
+                    let middle = deep.middle.push_back(node);
+                    Tree::deep(deep.front.clone(), middle, vec![d.clone(), item])
+                } else {
+                    let back = deep.back.iter().cloned().chain([item]);
+                    Tree::deep(deep.front.clone(), deep.middle.clone(), back.collect())
+                }
+            }
+        }
+    }
+
+    /// Splits off the first item, returning it and the rest of the tree.
+    fn pop_front(&self) -> Option<(Item<T>, Self)> {
+        let deep = match self {
+            Tree::Empty => return None,
+            Tree::Single(item) => return Some((item.clone(), Tree::Empty)),
+            Tree::Deep(deep) => deep,
+        };
+        let (first, rest) = deep.front.split_first().unwrap();
+        let tree = if !rest.is_empty() {
+            Tree::deep(rest.to_vec(), deep.middle.clone(), deep.back.clone())
+        } else if let Some((node, middle)) = deep.middle.pop_front() {
+            // An empty end is refilled with a node from the level below
+            Tree::deep(node.children().to_vec(), middle, deep.back.clone())
+        } else {
+            Tree::from_items(&deep.back)
+        };
+        Some((first.clone(), tree))
+    }
+
+    /// Splits off the last item, returning it and the rest of the tree.
+    fn pop_back(&self) -> Option<(Item<T>, Self)> {
+        let deep = match self {
+            Tree::Empty => return None,
+            Tree::Single(item) => return Some((item.clone(), Tree::Empty)),
+            Tree::Deep(deep) => deep,
+        };
+        let (last, rest) = deep.back.split_last().unwrap();
+        let tree = if !rest.is_empty() {
+            Tree::deep(deep.front.clone(), deep.middle.clone(), rest.to_vec())
+        } else if let Some((node, middle)) = deep.middle.pop_back() {
+            // See `pop_front`
+            Tree::deep(deep.front.clone(), middle, node.children().to_vec())
+        } else {
+            Tree::from_items(&deep.front)
+        };
+        Some((last.clone(), tree))
+    }
+}
+
+// Not derived, as cloning shares the elements instead of requiring `T: Clone`
+
+impl<T> Clone for Item<T> {
+    fn clone(&self) -> Self {
+        match self {
+            Item::Leaf(val) => Item::Leaf(Arc::clone(val)),
+            Item::Node(node) => Item::Node(Arc::clone(node)),
+        }
+    }
+}
+
+impl<T> Clone for Tree<T> {
+    fn clone(&self) -> Self {
+        match self {
+            Tree::Empty => Tree::Empty,
+            Tree::Single(item) => Tree::Single(item.clone()),
+            Tree::Deep(deep) => Tree::Deep(Arc::clone(deep)),
+        }
+    }
+}
+
+impl<T> Clone for PersistentList<T> {
+    /// Returns the same version of the list in O(1).
+    fn clone(&self) -> Self {
+        PersistentList {
+            tree: self.tree.clone(),
+        }
+    }
+}
+
+impl<T> Default for PersistentList<T> {
+    /// Creates an empty `PersistentList<T>`.
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for PersistentList<T> {
+    /// Formats the list as its elements, front to back, e.g. `[1, 2, 3]`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self).finish()
+    }
+}
+
+impl<T: PartialEq> PartialEq for PersistentList<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len() && (self.ptr_eq(other) || self.iter().eq(other))
+    }
+}
+
+impl<T: Eq> Eq for PersistentList<T> {}
+
+impl<T: Hash> Hash for PersistentList<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        // Prefix the length, like `DoublyLinkedList` does
+        state.write_usize(self.len());
+        for val in self {
+            val.hash(state);
+        }
+    }
+}
+
+impl<T> FromIterator<T> for PersistentList<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut list = PersistentList::new();
+        list.extend(iter);
+        list
+    }
+}
+
+impl<T> Extend<T> for PersistentList<T> {
+    /// Replaces this version with one that has the elements of `iter` added
+    /// to the back. Other versions are not affected.
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for val in iter {
+            self.tree = self.tree.push_back(Item::Leaf(Arc::new(val)));
+        }
+    }
+}
+
+impl<'a, T> IntoIterator for &'a PersistentList<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// Work left to an iterator: a tree or an item whose elements are yet to
+/// be yielded.
+enum Pending<'a, T> {
+    Tree(&'a Tree<T>),
+    Item(&'a Item<T>),
+}
+
+impl<T> Clone for Pending<'_, T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for Pending<'_, T> {}
+
+/// An iterator over the elements of a `PersistentList`.
+///
+/// This `struct` is created by [`PersistentList::iter`]. See its
+/// documentation for more.
+pub struct Iter<'a, T> {
+    // Both ends walk the whole tree on a stack of their own, and `len`
+    // stops them from yielding the same element twice
+    front: Vec<Pending<'a, T>>,
+    back: Vec<Pending<'a, T>>,
+    len: usize,
+}
+
+impl<T> Clone for Iter<'_, T> {
+    fn clone(&self) -> Self {
+        Iter {
+            front: self.front.clone(),
+            back: self.back.clone(),
+            len: self.len,
+        }
+    }
+}
+
+/// Pops work off `stack` until an element turns up, walking front to back
+/// if `forward`, back to front otherwise.
+fn next_leaf<'a, T>(stack: &mut Vec<Pending<'a, T>>, forward: bool) -> Option<&'a T> {
+    // Pushes `items` so that they are popped in walking order
+    fn push<'a, T>(stack: &mut Vec<Pending<'a, T>>, items: &'a [Item<T>], forward: bool) {
+        if forward {
+            stack.extend(items.iter().rev().map(Pending::Item));
+        } else {
+            stack.extend(items.iter().map(Pending::Item));
+        }
+    }
+    while let Some(pending) = stack.pop() {
+        match pending {
+            Pending::Item(Item::Leaf(val)) => return Some(val),
+            Pending::Item(item @ Item::Node(_)) => push(stack, item.children(), forward),
+            Pending::Tree(Tree::Empty) => {}
+            Pending::Tree(Tree::Single(item)) => stack.push(Pending::Item(item)),
+            Pending::Tree(Tree::Deep(deep)) => {
+                let (first, last) = if forward {
+                    (&deep.front, &deep.back)
+                } else {
+                    (&deep.back, &deep.front)
+                };
+                push(stack, last, forward);
+                stack.push(Pending::Tree(&deep.middle));
+                push(stack, first, forward);
+            }
+        }
+    }
+    None
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.len == 0 {
+            return None;
+        }
+        self.len -= 1;
+        next_leaf(&mut self.front, true)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<T> DoubleEndedIterator for Iter<'_, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.len == 0 {
+            return None;
+        }
+        self.len -= 1;
+        next_leaf(&mut self.back, false)
+    }
+}
+
+impl<T> ExactSizeIterator for Iter<'_, T> {}
+
+impl<T> FusedIterator for Iter<'_, T> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::VecDeque;
+
+    /// Checks the list against `model` through every way of reading it.
+    fn check(list: &PersistentList<u32>, model: &VecDeque<u32>) {
+        assert_eq!(list.len(), model.len());
+        assert!(list.iter().eq(model.iter()));
+        assert!(list.iter().rev().eq(model.iter().rev()));
+        assert_eq!((list.front(), list.back()), (model.front(), model.back()));
+        for (i, val) in model.iter().enumerate().step_by(7) {
+            assert_eq!(list.get(i), Some(val));
+        }
+        assert_eq!(list.get(model.len()), None);
+    }
+
+    #[test]
+    fn test_versions_stay_intact() {
+        let mut versions = vec![(PersistentList::new(), VecDeque::new())];
+        let mut rng = fastrand::Rng::with_seed(5);
+        for step in 0..3000 {
+            // Mostly extend one of the latest versions, sometimes an old one
+            let back = rng.usize(..versions.len()) / 256;
+            let (list, model) = &versions[versions.len() - 1 - back];
+            let (mut list, mut model) = (list.clone(), model.clone());
+            match rng.u32(..5) {
+                0 | 1 => {
+                    list = list.push_back(step);
+                    model.push_back(step);
+                }
+                2 => {
+                    list = list.push_front(step);
+                    model.push_front(step);
+                }
+                3 => {
+                    list = list.pop_front().unwrap_or_default();
+                    model.pop_front();
+                }
+                _ => {
+                    list = list.pop_back().unwrap_or_default();
+                    model.pop_back();
+                }
+            }
+            versions.push((list, model));
+        }
+        for (list, model) in &versions {
+            check(list, model);
+        }
+        assert!(versions.iter().any(|(list, _)| list.len() > 100));
+    }
+
+    #[test]
+    fn test_drain_from_both_ends() {
+        let list: PersistentList<_> = (0..1000).collect();
+        let mut model: VecDeque<_> = (0..1000).collect();
+        let mut rest = list.clone();
+        while let Some(popped) = rest.pop_front().and_then(|rest| rest.pop_back()) {
+            model.pop_front();
+            model.pop_back();
+            check(&popped, &model);
+            rest = popped;
+        }
+        assert_eq!(list.len(), 1000);
+        assert!(list.ptr_eq(&list.clone()));
+        assert_ne!(list, rest);
+
+        let mut iter = list.iter();
+        assert_eq!((iter.next(), iter.next_back()), (Some(&0), Some(&999)));
+        assert_eq!(iter.len(), 998);
+        assert_eq!(
+            iter.rev().step_by(100).copied().collect::<Vec<_>>().len(),
+            10
+        );
+    }
+
+    #[test]
+    fn test_versions_cross_threads() {
+        let list: PersistentList<String> = ["a", "b"].into_iter().map(String::from).collect();
+        let reader = list.clone();
+        let handle = std::thread::spawn(move || reader.iter().cloned().collect::<Vec<_>>());
+        let list = list.push_back("c".to_string());
+        assert_eq!(handle.join().unwrap(), ["a", "b"]);
+        assert_eq!(format!("{list:?}"), r#"["a", "b", "c"]"#);
+        assert_eq!(PersistentList::<String>::default(), PersistentList::new());
+    }
+}
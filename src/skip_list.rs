@@ -0,0 +1,833 @@
+//! An ordered map on a skip list, see [`SkipList`].
+//!
+//! The bottom level follows the design of `DoublyLinkedList`: leaked boxes
+//! linked both ways by plain pointers. It has a node type of its own rather
+//! than reusing the list's `Node<(K, V)>`, for two reasons:
+//!
+//! - Every node also carries its tower of forward links to the upper
+//!   levels. Keeping the tower inside the node costs one allocation per
+//!   entry and one pointer per step of a search. Keeping it outside, next
+//!   to a list node, would double both.
+//! - Removing an entry relinks every level of its tower together with the
+//!   bottom level, so the list's `unlink` would only ever do part of the
+//!   job. The list's nodes also reserve room for a `NodeHandle` slot that
+//!   entries of a map have no use for.
+
+use std::borrow::Borrow;
+use std::collections::hash_map::RandomState;
+use std::fmt;
+use std::hash::{BuildHasher, Hasher};
+use std::iter::FusedIterator;
+use std::marker::PhantomData;
+use std::mem;
+use std::ops::{Bound, RangeBounds};
+use std::ptr::NonNull;
+
+// With levels promoted with probability 1/2, 32 levels are plenty for any
+// list that fits in memory
+const MAX_LEVEL: usize = 32;
+
+type Link<K, V> = Option<NonNull<Node<K, V>>>;
+// The last node before a position on every level, `None` being the head
+type Path<K, V> = [Link<K, V>; MAX_LEVEL];
+
+/// Internal node of a `SkipList`.
+///
+/// Like the nodes of a `DoublyLinkedList`, every node is a leaked `Box`
+/// owned by the list and linked with plain pointers. The bottom level is
+/// linked both ways through `next[0]` and `prev`, the upper levels only
+/// forwards.
+struct Node<K, V> {
+    key: K,
+    val: V,
+    prev: Link<K, V>,
+    next: Box<[Link<K, V>]>, // One link per level the node is on
+}
+
+/// An ordered map on a skip list.
+///
+/// All entries are kept in a doubly linked bottom level sorted by key, so
+/// iterating is a walk in either direction. Every node is also put on
+/// each level above with probability 1/2, and the sparser upper levels let
+/// a search skip ahead, taking O(log n) expected steps to find a key.
+///
+/// ```
+/// use dll_rs::SkipList;
+///
+/// let mut scores = SkipList::new();
+/// scores.insert("carol", 7);
+/// scores.insert("alice", 9);
+/// scores.insert("bob", 3);
+/// assert_eq!(scores.get("bob"), Some(&3));
+/// assert_eq!(scores.first(), Some((&"alice", &9)));
+///
+/// let names: Vec<_> = scores.range("b"..).rev().map(|(name, _)| *name).collect();
+/// assert_eq!(names, ["carol", "bob"]);
+/// ```
+pub struct SkipList<K, V> {
+    head: Path<K, V>, // The first node on every level
+    tail: Link<K, V>,
+    len: usize,
+    // How many levels hold any nodes
+    levels: usize,
+    // State of the generator picking the heights of new nodes
+    seed: u64,
+    _marker: PhantomData<Box<Node<K, V>>>, // The list owns its nodes
+}
+
+// SAFETY: nodes are only reachable through the list that owns them, so
+// sending or sharing the list is like sending or sharing its entries.
+unsafe impl<K: Send, V: Send> Send for SkipList<K, V> {}
+unsafe impl<K: Sync, V: Sync> Sync for SkipList<K, V> {}
+
+impl<K, V> SkipList<K, V> {
+    /// Creates a new, empty skip list.
+    pub fn new() -> Self {
+        SkipList {
+            head: [None; MAX_LEVEL],
+            tail: None,
+            len: 0,
+            levels: 0,
+            // Any odd seed keeps xorshift from getting stuck at 0
+            seed: RandomState::new().build_hasher().finish() | 1,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Returns the number of entries in the list.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns true if the list contains no entries.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the entry with the smallest key, or `None` if the list is
+    /// empty.
+    pub fn first(&self) -> Option<(&K, &V)> {
+        // SAFETY: the node is owned by this list, which is borrowed
+        self.head[0].map(|node| unsafe { Node::entry(node) })
+    }
+
+    /// Returns the entry with the largest key, or `None` if the list is
+    /// empty.
+    pub fn last(&self) -> Option<(&K, &V)> {
+        // SAFETY: see `first`
+        self.tail.map(|node| unsafe { Node::entry(node) })
+    }
+
+    /// Returns an iterator over the entries in ascending order of their keys.
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        Iter {
+            head: self.head[0],
+            tail: self.tail,
+            len: self.len,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Returns an iterator over the entries in ascending order of their
+    /// keys, with mutable references to the values.
+    pub fn iter_mut(&mut self) -> IterMut<'_, K, V> {
+        IterMut {
+            head: self.head[0],
+            tail: self.tail,
+            len: self.len,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Removes all entries.
+    pub fn clear(&mut self) {
+        // Unlink the nodes one at a time, so the list stays whole if a
+        // destructor panics and `Drop` clears the rest
+        while let Some(node) = self.head[0] {
+            // SAFETY: the first node follows the head on every level it is on
+            drop(unsafe { self.unlink(&[None; MAX_LEVEL], node) });
+        }
+    }
+
+    /// Returns the link following `node` on `level`, where `None` is the
+    /// head.
+    ///
+    /// # Safety
+    /// `node` must be owned by this list and be on `level`.
+    unsafe fn next_at(&self, node: Link<K, V>, level: usize) -> Link<K, V> {
+        match node {
+            Some(node) => unsafe { (&(*node.as_ptr()).next)[level] },
+            None => self.head[level],
+        }
+    }
+
+    /// Sets the link following `node` on `level`, where `None` is the head.
+    ///
+    /// # Safety
+    /// See `next_at`.
+    unsafe fn set_next_at(&mut self, node: Link<K, V>, level: usize, next: Link<K, V>) {
+        match node {
+            Some(node) => unsafe { (&mut (*node.as_ptr()).next)[level] = next },
+            None => self.head[level] = next,
+        }
+    }
+
+    /// Finds the last node on every level for which `before` returns true.
+    /// `before` must return true for a prefix of the keys and false for the
+    /// rest.
+    fn path(&self, mut before: impl FnMut(&K) -> bool) -> Path<K, V> {
+        let mut path = [None; MAX_LEVEL];
+        let mut node = None;
+        for level in (0..self.levels).rev() {
+            // SAFETY: `node` is the head or was reached on this level
+            while let Some(next) = unsafe { self.next_at(node, level) } {
+                // SAFETY: the node is owned by this list, which is borrowed
+                if !before(unsafe { &(*next.as_ptr()).key }) {
+                    break;
+                }
+                node = Some(next);
+            }
+            path[level] = node;
+        }
+        path
+    }
+
+    /// Returns the first node on the bottom level after `path`.
+    fn after(&self, path: &Path<K, V>) -> Link<K, V> {
+        // SAFETY: paths only hold nodes found on their level
+        unsafe { self.next_at(path[0], 0) }
+    }
+
+    /// Picks how many levels a new node is on, each one with half the
+    /// chance of the last.
+    fn random_height(&mut self) -> usize {
+        // xorshift64
+        self.seed ^= self.seed << 13;
+        self.seed ^= self.seed >> 7;
+        self.seed ^= self.seed << 17;
+        (self.seed.trailing_ones() as usize + 1).min(MAX_LEVEL)
+    }
+
+    /// Links a new node for `key` and `val` in after `path`, and returns
+    /// how many levels it is on.
+    fn link_after(&mut self, path: &Path<K, V>, key: K, val: V) -> usize {
+        let height = self.random_height();
+        // Levels above `levels` are empty, so their path is the head already
+        self.levels = self.levels.max(height);
+        let node = NonNull::from(Box::leak(Box::new(Node {
+            key,
+            val,
+            prev: path[0],
+            next: vec![None; height].into_boxed_slice(),
+        })));
+        for (level, &before) in path.iter().enumerate().take(height) {
+            // SAFETY: `before` is on `level`, and the new node has a link
+            // for every level below its height
+            unsafe {
+                let next = self.next_at(before, level);
+                (&mut (*node.as_ptr()).next)[level] = next;
+                self.set_next_at(before, level, Some(node));
+            }
+        }
+        // SAFETY: the new node is linked in, and its successor belongs to
+        // this list
+        match unsafe { (&(*node.as_ptr()).next)[0] } {
+            Some(next) => unsafe { (*next.as_ptr()).prev = Some(node) },
+            None => self.tail = Some(node),
+        }
+        self.len += 1;
+        height
+    }
+
+    /// Unlinks `node`, the node right after `path`, and frees it.
+    ///
+    /// # Safety
+    /// `node` must be owned by this list and `path` must lead up to it on
+    /// every level.
+    unsafe fn unlink(&mut self, path: &Path<K, V>, node: NonNull<Node<K, V>>) -> (K, V) {
+        let node = unsafe { Box::from_raw(node.as_ptr()) };
+        for (level, &next) in node.next.iter().enumerate() {
+            // SAFETY: `path[level]` is linked to `node` on `level`
+            unsafe { self.set_next_at(path[level], level, next) };
+        }
+        match node.next[0] {
+            // SAFETY: the successor belongs to this list
+            Some(next) => unsafe { (*next.as_ptr()).prev = node.prev },
+            None => self.tail = node.prev,
+        }
+        while self.levels > 0 && self.head[self.levels - 1].is_none() {
+            self.levels -= 1;
+        }
+        self.len -= 1;
+        (node.key, node.val)
+    }
+}
+
+impl<K: Ord, V> SkipList<K, V> {
+    /// Inserts `val` under `key`, returning the value it replaces.
+    pub fn insert(&mut self, key: K, val: V) -> Option<V> {
+        let path = self.path(|k| k < &key);
+        if let Some(node) = self.after(&path) {
+            // SAFETY: the node is owned by this list, which is borrowed
+            // mutably
+            let node = unsafe { &mut *node.as_ptr() };
+            if node.key == key {
+                return Some(mem::replace(&mut node.val, val));
+            }
+        }
+        self.link_after(&path, key, val);
+        None
+    }
+
+    /// Removes the entry of `key` and returns its value.
+    pub fn remove<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let path = self.path(|k| k.borrow() < key);
+        let node = self.after(&path)?;
+        // SAFETY: the node is owned by this list, right after `path`
+        unsafe {
+            if (*node.as_ptr()).key.borrow() != key {
+                return None;
+            }
+            Some(self.unlink(&path, node).1)
+        }
+    }
+
+    /// Returns a reference to the value of `key`.
+    pub fn get<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let node = self.find(key)?;
+        // SAFETY: the node is owned by this list, which is borrowed
+        unsafe { Some(&(*node.as_ptr()).val) }
+    }
+
+    /// Returns a mutable reference to the value of `key`.
+    pub fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let node = self.find(key)?;
+        // SAFETY: the node is owned by this list, which is borrowed mutably
+        unsafe { Some(&mut (*node.as_ptr()).val) }
+    }
+
+    /// Returns true if the list contains an entry for `key`.
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.find(key).is_some()
+    }
+
+    /// Removes the entry with the smallest key and returns it.
+    pub fn pop_first(&mut self) -> Option<(K, V)> {
+        let node = self.head[0]?;
+        // SAFETY: the first node follows the head on every level it is on
+        unsafe { Some(self.unlink(&[None; MAX_LEVEL], node)) }
+    }
+
+    /// Removes the entry with the largest key and returns it.
+    pub fn pop_last(&mut self) -> Option<(K, V)> {
+        let node = self.tail?;
+        // SAFETY: the node is owned by this list, which is borrowed
+        let key = unsafe { &(*node.as_ptr()).key };
+        let path = self.path(|k| k < key);
+        // SAFETY: `path` leads up to the last node
+        unsafe { Some(self.unlink(&path, node)) }
+    }
+
+    /// Returns a double-ended iterator over the entries whose keys are
+    /// within `range`, in ascending order of their keys.
+    /// ```
+    /// use dll_rs::SkipList;
+    ///
+    /// let list: SkipList<_, _> = (0..10).map(|i| (i, i * i)).collect();
+    /// let squares: Vec<_> = list.range(3..6).map(|(_, sq)| *sq).collect();
+    /// assert_eq!(squares, [9, 16, 25]);
+    /// assert_eq!(list.range(..=2).next_back(), Some((&2, &4)));
+    /// ```
+    pub fn range<Q, R>(&self, range: R) -> Range<'_, K, V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+        R: RangeBounds<Q>,
+    {
+        let front = match range.start_bound() {
+            Bound::Included(start) => self.after(&self.path(|k| k.borrow() < start)),
+            Bound::Excluded(start) => self.after(&self.path(|k| k.borrow() <= start)),
+            Bound::Unbounded => self.head[0],
+        };
+        let back = match range.end_bound() {
+            Bound::Included(end) => self.path(|k| k.borrow() <= end)[0],
+            Bound::Excluded(end) => self.path(|k| k.borrow() < end)[0],
+            Bound::Unbounded => self.tail,
+        };
+        let empty = match (front, back) {
+            // SAFETY: both nodes are owned by this list, which is borrowed
+            (Some(front), Some(back)) => unsafe { (*front.as_ptr()).key > (*back.as_ptr()).key },
+            _ => true,
+        };
+        if empty {
+            return Range {
+                front: None,
+                back: None,
+                _marker: PhantomData,
+            };
+        }
+        Range {
+            front,
+            back,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Returns the node holding `key`.
+    fn find<Q>(&self, key: &Q) -> Link<K, V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let node = self.after(&self.path(|k| k.borrow() < key))?;
+        // SAFETY: the node is owned by this list, which is borrowed
+        unsafe { ((*node.as_ptr()).key.borrow() == key).then_some(node) }
+    }
+}
+
+impl<K, V> Node<K, V> {
+    /// Returns references to the key and value of `node`.
+    ///
+    /// # Safety
+    /// `node` must be owned by a list that is borrowed for `'a`.
+    unsafe fn entry<'a>(node: NonNull<Self>) -> (&'a K, &'a V) {
+        let node = unsafe { &*node.as_ptr() };
+        (&node.key, &node.val)
+    }
+}
+
+impl<K, V> Default for SkipList<K, V> {
+    /// Creates an empty `SkipList<K, V>`.
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K, V> Drop for SkipList<K, V> {
+    fn drop(&mut self) {
+        self.clear();
+    }
+}
+
+impl<K: fmt::Debug, V: fmt::Debug> fmt::Debug for SkipList<K, V> {
+    /// Formats the list as a map in ascending order of its keys.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self).finish()
+    }
+}
+
+impl<K: Ord + Clone, V: Clone> Clone for SkipList<K, V> {
+    /// Returns a deep copy of the list; the copy shares no nodes with `self`.
+    fn clone(&self) -> Self {
+        let mut list = SkipList::new();
+        let mut path = [None; MAX_LEVEL];
+        for (key, val) in self {
+            // Keys come in order, so each node goes after the last one
+            let height = list.link_after(&path, key.clone(), val.clone());
+            path[..height].fill(list.tail);
+        }
+        list
+    }
+}
+
+impl<K: Ord, V> FromIterator<(K, V)> for SkipList<K, V> {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut list = SkipList::new();
+        list.extend(iter);
+        list
+    }
+}
+
+impl<K: Ord, V> Extend<(K, V)> for SkipList<K, V> {
+    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+        iter.into_iter().for_each(|(key, val)| {
+            self.insert(key, val);
+        });
+    }
+}
+
+impl<'a, K, V> IntoIterator for &'a SkipList<K, V> {
+    type Item = (&'a K, &'a V);
+    type IntoIter = Iter<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, K, V> IntoIterator for &'a mut SkipList<K, V> {
+    type Item = (&'a K, &'a mut V);
+    type IntoIter = IterMut<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+impl<K: Ord, V> IntoIterator for SkipList<K, V> {
+    type Item = (K, V);
+    type IntoIter = IntoIter<K, V>;
+
+    /// Consumes the list into an iterator yielding entries by value.
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter { list: self }
+    }
+}
+
+/// An iterator over the entries of a `SkipList`.
+///
+/// This `struct` is created by [`SkipList::iter`]. See its documentation
+/// for more.
+pub struct Iter<'a, K, V> {
+    head: Link<K, V>,
+    tail: Link<K, V>,
+    len: usize,
+    _marker: PhantomData<&'a Node<K, V>>,
+}
+
+impl<K, V> Clone for Iter<'_, K, V> {
+    fn clone(&self) -> Self {
+        Iter { ..*self }
+    }
+}
+
+impl<'a, K, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.len == 0 {
+            return None;
+        }
+        self.head.map(|node| {
+            self.len -= 1;
+            // SAFETY: the list, and with it every node, is borrowed for 'a
+            unsafe {
+                self.head = (&(*node.as_ptr()).next)[0];
+                Node::entry(node)
+            }
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<K, V> DoubleEndedIterator for Iter<'_, K, V> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.len == 0 {
+            return None;
+        }
+        self.tail.map(|node| {
+            self.len -= 1;
+            // SAFETY: see `next`.
+            unsafe {
+                self.tail = (*node.as_ptr()).prev;
+                Node::entry(node)
+            }
+        })
+    }
+}
+
+impl<K, V> ExactSizeIterator for Iter<'_, K, V> {}
+
+impl<K, V> FusedIterator for Iter<'_, K, V> {}
+
+/// An iterator over the entries of a `SkipList`, with mutable references
+/// to the values.
+///
+/// This `struct` is created by [`SkipList::iter_mut`]. See its
+/// documentation for more.
+pub struct IterMut<'a, K, V> {
+    head: Link<K, V>,
+    tail: Link<K, V>,
+    len: usize,
+    _marker: PhantomData<&'a mut Node<K, V>>,
+}
+
+impl<'a, K, V> Iterator for IterMut<'a, K, V> {
+    type Item = (&'a K, &'a mut V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.len == 0 {
+            return None;
+        }
+        self.head.map(|node| {
+            let node = node.as_ptr();
+            self.len -= 1;
+            // SAFETY: the list is exclusively borrowed for 'a and every node
+            // is yielded at most once, so the value reference is unique
+            unsafe {
+                self.head = (&(*node).next)[0];
+                (&(*node).key, &mut (*node).val)
+            }
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<K, V> DoubleEndedIterator for IterMut<'_, K, V> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.len == 0 {
+            return None;
+        }
+        self.tail.map(|node| {
+            let node = node.as_ptr();
+            self.len -= 1;
+            // SAFETY: see `next`.
+            unsafe {
+                self.tail = (*node).prev;
+                (&(*node).key, &mut (*node).val)
+            }
+        })
+    }
+}
+
+impl<K, V> ExactSizeIterator for IterMut<'_, K, V> {}
+
+impl<K, V> FusedIterator for IterMut<'_, K, V> {}
+
+/// An iterator over a range of entries of a `SkipList`.
+///
+/// This `struct` is created by [`SkipList::range`]. See its documentation
+/// for more.
+pub struct Range<'a, K, V> {
+    // Both `None` once the range is used up, else the next entries to yield
+    // from either end
+    front: Link<K, V>,
+    back: Link<K, V>,
+    _marker: PhantomData<&'a Node<K, V>>,
+}
+
+impl<K, V> Clone for Range<'_, K, V> {
+    fn clone(&self) -> Self {
+        Range { ..*self }
+    }
+}
+
+impl<'a, K, V> Iterator for Range<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.front?;
+        if self.front == self.back {
+            self.front = None;
+            self.back = None;
+        } else {
+            // SAFETY: the list, and with it every node, is borrowed for 'a
+            self.front = unsafe { (&(*node.as_ptr()).next)[0] };
+        }
+        // SAFETY: see above
+        Some(unsafe { Node::entry(node) })
+    }
+}
+
+impl<K, V> DoubleEndedIterator for Range<'_, K, V> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let node = self.back?;
+        if self.front == self.back {
+            self.front = None;
+            self.back = None;
+        } else {
+            // SAFETY: see `next`.
+            self.back = unsafe { (*node.as_ptr()).prev };
+        }
+        // SAFETY: see `next`.
+        Some(unsafe { Node::entry(node) })
+    }
+}
+
+impl<K, V> FusedIterator for Range<'_, K, V> {}
+
+/// An owning iterator over the entries of a `SkipList`.
+///
+/// This `struct` is created by the `into_iter` method on `SkipList`.
+pub struct IntoIter<K, V> {
+    list: SkipList<K, V>,
+}
+
+impl<K: Ord, V> Iterator for IntoIter<K, V> {
+    type Item = (K, V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.list.pop_first()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.list.len, Some(self.list.len))
+    }
+}
+
+impl<K: Ord, V> DoubleEndedIterator for IntoIter<K, V> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.list.pop_last()
+    }
+}
+
+impl<K: Ord, V> ExactSizeIterator for IntoIter<K, V> {}
+
+impl<K: Ord, V> FusedIterator for IntoIter<K, V> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+    use std::panic::{self, AssertUnwindSafe};
+
+    /// Checks the links on every level against the bottom one.
+    fn check_levels<K: Ord, V>(list: &SkipList<K, V>) {
+        let mut prev = None;
+        let mut node = list.head[0];
+        let mut len = 0;
+        while let Some(n) = node {
+            let n = unsafe { &*n.as_ptr() };
+            assert_eq!(n.prev, prev);
+            assert!(n.next.len() <= list.levels);
+            prev = node;
+            node = n.next[0];
+            len += 1;
+        }
+        assert_eq!(list.tail, prev);
+        assert_eq!(list.len, len);
+        for level in 0..MAX_LEVEL {
+            let keys: Vec<_> = {
+                let mut keys = Vec::new();
+                let mut node = list.head[level];
+                while let Some(n) = node {
+                    let n = unsafe { &*n.as_ptr() };
+                    keys.push(&n.key);
+                    node = n.next[level];
+                }
+                keys
+            };
+            assert!(keys.is_sorted_by(|a, b| a < b));
+            assert_eq!(keys.is_empty(), level >= list.levels);
+        }
+    }
+
+    #[test]
+    fn test_matches_btree_map() {
+        let mut list = SkipList::new();
+        let mut model = BTreeMap::new();
+        let mut rng = fastrand::Rng::with_seed(11);
+        for step in 0..2000 {
+            let key = rng.u32(..300);
+            if step % 3 == 2 {
+                assert_eq!(list.remove(&key), model.remove(&key));
+            } else {
+                assert_eq!(list.insert(key, step), model.insert(key, step));
+            }
+            assert_eq!(list.get(&key), model.get(&key));
+        }
+        check_levels(&list);
+        assert!(list.levels > 1);
+        assert!(list.iter().eq(model.iter()));
+        assert!(list.iter().rev().eq(model.iter().rev()));
+        assert_eq!(list.first(), model.first_key_value());
+        assert_eq!(list.last(), model.last_key_value());
+
+        for (start, end) in [(0, 300), (50, 60), (120, 121), (200, 100), (299, 400)] {
+            assert!(
+                list.range(start..end)
+                    .eq(model.range(start..end.max(start)))
+            );
+            assert!(
+                list.range(start..=end)
+                    .rev()
+                    .eq(model.range(start..=end.max(start)).rev())
+            );
+        }
+        assert!(
+            list.range((Bound::Excluded(10), Bound::Unbounded))
+                .eq(model.range((Bound::Excluded(10), Bound::Unbounded)))
+        );
+
+        while !list.is_empty() {
+            assert_eq!(list.pop_first(), model.pop_first());
+            assert_eq!(list.pop_last(), model.pop_last());
+        }
+        check_levels(&list);
+        assert_eq!(list.levels, 0);
+    }
+
+    #[test]
+    fn test_range_meets_in_the_middle() {
+        let list: SkipList<_, _> = (0..5).map(|i| (i, ())).collect();
+        let mut range = list.range(1..4);
+        assert_eq!(range.next().map(|(k, _)| *k), Some(1));
+        assert_eq!(range.next_back().map(|(k, _)| *k), Some(3));
+        assert_eq!(range.clone().next_back().map(|(k, _)| *k), Some(2));
+        assert_eq!(range.next().map(|(k, _)| *k), Some(2));
+        assert!(range.next_back().is_none());
+        assert!(range.next().is_none());
+        assert!(list.range(5..).next().is_none());
+        assert!(SkipList::<i32, ()>::new().range(..).next().is_none());
+    }
+
+    #[test]
+    fn test_borrowed_keys_and_mutation() {
+        let mut list = SkipList::new();
+        for word in ["pear", "apple", "fig"] {
+            list.insert(word.to_string(), word.len());
+        }
+        *list.get_mut("fig").unwrap() += 10;
+        list.iter_mut().for_each(|(_, len)| *len *= 2);
+        assert_eq!(list.get("fig"), Some(&26));
+        assert!(list.contains_key("pear"));
+        assert_eq!(list.remove("pear"), Some(8));
+        assert_eq!(list.remove("pear"), None);
+
+        let copy = list.clone();
+        check_levels(&copy);
+        list.clear();
+        assert!(list.is_empty());
+        assert_eq!(format!("{copy:?}"), r#"{"apple": 10, "fig": 26}"#);
+        let entries: Vec<_> = copy.into_iter().rev().collect();
+        assert_eq!(
+            entries,
+            [("fig".to_string(), 26), ("apple".to_string(), 10)]
+        );
+    }
+
+    #[test]
+    fn test_clear_survives_panicking_drop() {
+        struct PanicOnDrop(bool);
+
+        impl Drop for PanicOnDrop {
+            fn drop(&mut self) {
+                if self.0 {
+                    panic!("dropping a value that panics");
+                }
+            }
+        }
+
+        let mut list = SkipList::new();
+        list.insert(1, PanicOnDrop(true));
+        list.insert(2, PanicOnDrop(false));
+        let result = panic::catch_unwind(AssertUnwindSafe(|| list.clear()));
+        assert!(result.is_err());
+        assert_eq!(list.len(), 1);
+        assert_eq!(list.first().map(|(k, _)| *k), Some(2));
+        list.clear();
+        assert!(list.is_empty());
+    }
+}
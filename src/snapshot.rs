@@ -0,0 +1,541 @@
+//! A compact binary snapshot format for `DoublyLinkedList`.
+//!
+//! A snapshot is laid out as follows, with every integer little-endian:
+//!
+//! | field    | size      | contents                                        |
+//! |----------|-----------|-------------------------------------------------|
+//! | magic    | 4 bytes   | `b"DLLS"`                                       |
+//! | version  | 2 bytes   | [`FORMAT_VERSION`]                              |
+//! | count    | 8 bytes   | the number of elements                          |
+//! | elements | `count` × | a 4 byte length, then that many bytes of [`Codec::encode`] output |
+//! | checksum | 4 bytes   | CRC-32 (IEEE) of every byte before it           |
+//!
+//! Elements are stored front to back. The length prefixes let a reader
+//! frame every element without knowing its codec, and the checksum covers
+//! the header too, so a truncated or corrupted snapshot is always reported
+//! as a [`SnapshotError`] instead of being loaded in part.
+
+use std::error::Error;
+use std::fmt;
+use std::io::{self, Read, Write};
+
+use crate::DoublyLinkedList;
+
+const MAGIC: [u8; 4] = *b"DLLS";
+
+/// The version of the snapshot format written by
+/// [`DoublyLinkedList::write_snapshot`].
+pub const FORMAT_VERSION: u16 = 1;
+
+/// Encodes values to bytes and decodes them back, for snapshots.
+///
+/// Implementations only need to agree with themselves: `decode` is handed
+/// exactly the bytes one call to `encode` appended.
+///
+/// ```
+/// use dll_rs::snapshot::{Codec, DecodeError};
+///
+/// struct Point(i32, i32);
+///
+/// impl Codec for Point {
+///     fn encode(&self, buf: &mut Vec<u8>) {
+///         (self.0, self.1).encode(buf);
+///     }
+///
+///     fn decode(bytes: &[u8]) -> Result<Self, DecodeError> {
+///         let (x, y) = Codec::decode(bytes)?;
+///         Ok(Point(x, y))
+///     }
+/// }
+/// ```
+pub trait Codec: Sized {
+    /// Appends the encoding of `self` to `buf`.
+    fn encode(&self, buf: &mut Vec<u8>);
+
+    /// Decodes a value from the bytes written by `encode`.
+    fn decode(bytes: &[u8]) -> Result<Self, DecodeError>;
+}
+
+/// Error returned by [`Codec::decode`] for bytes that are not a valid
+/// encoding.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DecodeError;
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("invalid encoding")
+    }
+}
+
+impl Error for DecodeError {}
+
+/// Error returned when a snapshot could not be read.
+#[derive(Debug)]
+pub enum SnapshotError {
+    /// Reading failed.
+    Io(io::Error),
+    /// The input does not start like a snapshot.
+    BadMagic,
+    /// The snapshot was written in a format version this build cannot read.
+    UnsupportedVersion(u16),
+    /// The input ended before the checksum.
+    Truncated,
+    /// The checksum does not match the contents, which are corrupted.
+    ChecksumMismatch { expected: u32, actual: u32 },
+    /// The checksum matched, but the element at `index` could not be
+    /// decoded. The snapshot was likely written for a different type.
+    Decode { index: u64 },
+}
+
+impl fmt::Display for SnapshotError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SnapshotError::Io(err) => write!(f, "failed to read snapshot: {err}"),
+            SnapshotError::BadMagic => f.write_str("not a snapshot"),
+            SnapshotError::UnsupportedVersion(version) => {
+                write!(f, "unsupported snapshot format version {version}")
+            }
+            SnapshotError::Truncated => f.write_str("snapshot is truncated"),
+            SnapshotError::ChecksumMismatch { expected, actual } => write!(
+                f,
+                "snapshot checksum is {actual:#010x}, expected {expected:#010x}"
+            ),
+            SnapshotError::Decode { index } => {
+                write!(f, "element {index} of the snapshot could not be decoded")
+            }
+        }
+    }
+}
+
+impl Error for SnapshotError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            SnapshotError::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for SnapshotError {
+    fn from(err: io::Error) -> Self {
+        match err.kind() {
+            io::ErrorKind::UnexpectedEof => SnapshotError::Truncated,
+            _ => SnapshotError::Io(err),
+        }
+    }
+}
+
+impl<T: Codec> DoublyLinkedList<T> {
+    /// Writes a snapshot of the list to `w`, in the format described in the
+    /// [`snapshot`](crate::snapshot) module.
+    ///
+    /// The snapshot is written in many small pieces, so `w` should be
+    /// buffered.
+    ///
+    /// # Errors
+    /// Fails if writing fails, or with [`io::ErrorKind::InvalidInput`] if an
+    /// element encodes to 4 GiB or more.
+    ///
+    /// # Panics
+    /// Panics if an element is a `Vec` or a tuple with a part that encodes to
+    /// 4 GiB or more, see their [`Codec`] implementations.
+    /// ```
+    /// use dll_rs::DoublyLinkedList;
+    ///
+    /// let list = DoublyLinkedList::from(["queued".to_string(), "jobs".to_string()]);
+    /// let mut file = Vec::new();
+    /// list.write_snapshot(&mut file)?;
+    ///
+    /// let restored = DoublyLinkedList::<String>::read_snapshot(&file[..])?;
+    /// assert_eq!(restored, list);
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn write_snapshot(&self, w: impl Write) -> io::Result<()> {
+        let mut w = Checksummed::new(w);
+        w.write_all(&MAGIC)?;
+        w.write_all(&FORMAT_VERSION.to_le_bytes())?;
+        w.write_all(&(self.len() as u64).to_le_bytes())?;
+        let mut buf = Vec::new();
+        for val in self {
+            buf.clear();
+            val.encode(&mut buf);
+            let len = u32::try_from(buf.len()).map_err(|_| {
+                io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "element too large for a snapshot",
+                )
+            })?;
+            w.write_all(&len.to_le_bytes())?;
+            w.write_all(&buf)?;
+        }
+        let checksum = w.crc.finish();
+        w.inner.write_all(&checksum.to_le_bytes())?;
+        w.inner.flush()
+    }
+
+    /// Reads a list back from a snapshot written by
+    /// [`DoublyLinkedList::write_snapshot`], pushing every element to the
+    /// back as it is decoded.
+    ///
+    /// Reading stops right after the checksum, so `r` may hold more data
+    /// after the snapshot. `r` should be buffered.
+    ///
+    /// # Errors
+    /// Fails with a [`SnapshotError`] if the snapshot cannot be read in
+    /// full. No partially read list is ever returned.
+    pub fn read_snapshot(r: impl Read) -> Result<Self, SnapshotError> {
+        let mut r = Checksummed::new(r);
+        let mut magic = [0; 4];
+        r.read_exact(&mut magic)?;
+        if magic != MAGIC {
+            return Err(SnapshotError::BadMagic);
+        }
+        let version = u16::from_le_bytes(r.read_array()?);
+        if version != FORMAT_VERSION {
+            return Err(SnapshotError::UnsupportedVersion(version));
+        }
+        let count = u64::from_le_bytes(r.read_array()?);
+        let mut list = DoublyLinkedList::new();
+        let mut failed = None;
+        let mut buf = Vec::new();
+        for index in 0..count {
+            let len = u32::from_le_bytes(r.read_array()?);
+            buf.clear();
+            // Reads in chunks, so a corrupted length cannot allocate more
+            // than the input holds
+            (&mut r).take(len.into()).read_to_end(&mut buf)?;
+            if buf.len() != len as usize {
+                return Err(SnapshotError::Truncated);
+            }
+            // A decoding error is only reported once the checksum is known
+            // to match, as corruption is the likelier cause
+            if failed.is_none() {
+                match T::decode(&buf) {
+                    Ok(val) => list.push_back(val),
+                    Err(DecodeError) => failed = Some(index),
+                }
+            }
+        }
+        let actual = r.crc.finish();
+        let mut expected = [0; 4];
+        r.inner.read_exact(&mut expected)?;
+        let expected = u32::from_le_bytes(expected);
+        if expected != actual {
+            return Err(SnapshotError::ChecksumMismatch { expected, actual });
+        }
+        match failed {
+            Some(index) => Err(SnapshotError::Decode { index }),
+            None => Ok(list),
+        }
+    }
+}
+
+/// A reader or writer that keeps a running checksum of the bytes passing
+/// through it.
+struct Checksummed<I> {
+    inner: I,
+    crc: Crc32,
+}
+
+impl<I> Checksummed<I> {
+    fn new(inner: I) -> Self {
+        Checksummed {
+            inner,
+            crc: Crc32::new(),
+        }
+    }
+}
+
+impl<R: Read> Checksummed<R> {
+    fn read_array<const N: usize>(&mut self) -> io::Result<[u8; N]> {
+        let mut bytes = [0; N];
+        self.read_exact(&mut bytes)?;
+        Ok(bytes)
+    }
+}
+
+impl<R: Read> Read for Checksummed<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.crc.update(&buf[..n]);
+        Ok(n)
+    }
+}
+
+impl<W: Write> Write for Checksummed<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let n = self.inner.write(buf)?;
+        self.crc.update(&buf[..n]);
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+// Lookup table for the reflected IEEE polynomial, one entry per byte
+const CRC_TABLE: [u32; 256] = {
+    let mut table = [0; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = i as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 1 == 1 {
+                0xEDB8_8320 ^ (crc >> 1)
+            } else {
+                crc >> 1
+            };
+            bit += 1;
+        }
+        table[i] = crc;
+        i += 1;
+    }
+    table
+};
+
+/// A running CRC-32 (IEEE), the checksum used by zip and PNG.
+#[derive(Clone, Copy)]
+pub(crate) struct Crc32(u32);
+
+impl Crc32 {
+    pub(crate) fn new() -> Self {
+        Crc32(!0)
+    }
+
+    pub(crate) fn update(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.0 = CRC_TABLE[((self.0 ^ byte as u32) & 0xFF) as usize] ^ (self.0 >> 8);
+        }
+    }
+
+    pub(crate) fn finish(self) -> u32 {
+        !self.0
+    }
+}
+
+macro_rules! impl_codec_for_num {
+    ($($ty:ty),*) => {$(
+        impl Codec for $ty {
+            fn encode(&self, buf: &mut Vec<u8>) {
+                buf.extend_from_slice(&self.to_le_bytes());
+            }
+
+            fn decode(bytes: &[u8]) -> Result<Self, DecodeError> {
+                bytes.try_into().map(Self::from_le_bytes).map_err(|_| DecodeError)
+            }
+        }
+    )*};
+}
+
+impl_codec_for_num!(u8, u16, u32, u64, u128, i8, i16, i32, i64, i128, f32, f64);
+
+impl Codec for usize {
+    /// Encodes the value as a `u64`, so snapshots are portable between
+    /// platforms.
+    fn encode(&self, buf: &mut Vec<u8>) {
+        (*self as u64).encode(buf);
+    }
+
+    fn decode(bytes: &[u8]) -> Result<Self, DecodeError> {
+        u64::decode(bytes)?.try_into().map_err(|_| DecodeError)
+    }
+}
+
+impl Codec for bool {
+    fn encode(&self, buf: &mut Vec<u8>) {
+        buf.push(*self as u8);
+    }
+
+    fn decode(bytes: &[u8]) -> Result<Self, DecodeError> {
+        match bytes {
+            [0] => Ok(false),
+            [1] => Ok(true),
+            _ => Err(DecodeError),
+        }
+    }
+}
+
+impl Codec for char {
+    fn encode(&self, buf: &mut Vec<u8>) {
+        u32::from(*self).encode(buf);
+    }
+
+    fn decode(bytes: &[u8]) -> Result<Self, DecodeError> {
+        char::from_u32(u32::decode(bytes)?).ok_or(DecodeError)
+    }
+}
+
+impl Codec for String {
+    fn encode(&self, buf: &mut Vec<u8>) {
+        buf.extend_from_slice(self.as_bytes());
+    }
+
+    fn decode(bytes: &[u8]) -> Result<Self, DecodeError> {
+        String::from_utf8(bytes.to_vec()).map_err(|_| DecodeError)
+    }
+}
+
+impl<T: Codec> Codec for Option<T> {
+    fn encode(&self, buf: &mut Vec<u8>) {
+        if let Some(val) = self {
+            buf.push(1);
+            val.encode(buf);
+        } else {
+            buf.push(0);
+        }
+    }
+
+    fn decode(bytes: &[u8]) -> Result<Self, DecodeError> {
+        match bytes.split_first() {
+            Some((0, [])) => Ok(None),
+            Some((1, val)) => T::decode(val).map(Some),
+            _ => Err(DecodeError),
+        }
+    }
+}
+
+impl<T: Codec> Codec for Vec<T> {
+    /// Encodes the elements one after the other, each prefixed with its
+    /// length like the elements of a snapshot.
+    ///
+    /// # Panics
+    /// Panics if an element encodes to 4 GiB or more, as its length would
+    /// not fit its prefix and `encode` cannot fail.
+    fn encode(&self, buf: &mut Vec<u8>) {
+        self.iter().for_each(|val| encode_framed(val, buf));
+    }
+
+    fn decode(mut bytes: &[u8]) -> Result<Self, DecodeError> {
+        let mut vec = Vec::new();
+        while !bytes.is_empty() {
+            vec.push(decode_framed(&mut bytes)?);
+        }
+        Ok(vec)
+    }
+}
+
+impl<A: Codec, B: Codec> Codec for (A, B) {
+    /// Encodes the first value prefixed with its length, then the second.
+    ///
+    /// # Panics
+    /// Panics if the first value encodes to 4 GiB or more, like the
+    /// elements of a `Vec`.
+    fn encode(&self, buf: &mut Vec<u8>) {
+        encode_framed(&self.0, buf);
+        self.1.encode(buf);
+    }
+
+    fn decode(mut bytes: &[u8]) -> Result<Self, DecodeError> {
+        let a = decode_framed(&mut bytes)?;
+        Ok((a, B::decode(bytes)?))
+    }
+}
+
+/// Appends the encoding of `val` to `buf`, prefixed with its length.
+/// Panics if the encoding does not fit the prefix.
+fn encode_framed<T: Codec>(val: &T, buf: &mut Vec<u8>) {
+    let start = buf.len();
+    buf.extend_from_slice(&[0; 4]);
+    val.encode(buf);
+    let len = u32::try_from(buf.len() - start - 4).expect("encoding is 4 GiB or more");
+    buf[start..start + 4].copy_from_slice(&len.to_le_bytes());
+}
+
+/// Decodes a value written by `encode_framed` off the front of `bytes`.
+fn decode_framed<T: Codec>(bytes: &mut &[u8]) -> Result<T, DecodeError> {
+    let (len, rest) = bytes.split_first_chunk::<4>().ok_or(DecodeError)?;
+    let len = u32::from_le_bytes(*len) as usize;
+    if rest.len() < len {
+        return Err(DecodeError);
+    }
+    let (val, rest) = rest.split_at(len);
+    *bytes = rest;
+    T::decode(val)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snapshot<T: Codec>(list: &DoublyLinkedList<T>) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        list.write_snapshot(&mut bytes).unwrap();
+        bytes
+    }
+
+    #[test]
+    fn test_crc32_check_value() {
+        let mut crc = Crc32::new();
+        crc.update(b"1234");
+        crc.update(b"56789");
+        assert_eq!(crc.finish(), 0xCBF4_3926);
+        assert_eq!(Crc32::new().finish(), 0);
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let list: DoublyLinkedList<_> = (0..100_u32)
+            .map(|i| (i, (i % 3 == 0).then(|| vec!['x'; i as usize % 5])))
+            .collect();
+        let mut bytes = snapshot(&list);
+        assert_eq!(&bytes[..6], b"DLLS\x01\x00");
+        // Anything after the snapshot is left unread
+        bytes.extend_from_slice(b"trailer");
+        let mut r = &bytes[..];
+        assert_eq!(DoublyLinkedList::read_snapshot(&mut r).unwrap(), list);
+        assert_eq!(r, b"trailer");
+
+        let empty = DoublyLinkedList::<String>::new();
+        assert!(
+            DoublyLinkedList::<String>::read_snapshot(&snapshot(&empty)[..])
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_damaged_snapshots_are_rejected() {
+        let list = DoublyLinkedList::from(["a".to_string(), "bc".to_string()]);
+        let bytes = snapshot(&list);
+        let read = |bytes: &[u8]| DoublyLinkedList::<String>::read_snapshot(bytes).unwrap_err();
+
+        for len in 0..bytes.len() {
+            assert!(matches!(read(&bytes[..len]), SnapshotError::Truncated));
+        }
+        for i in 6..bytes.len() {
+            let mut corrupted = bytes.clone();
+            corrupted[i] ^= 0x40;
+            assert!(
+                matches!(
+                    read(&corrupted),
+                    SnapshotError::ChecksumMismatch { .. } | SnapshotError::Truncated
+                ),
+                "flipped a bit of byte {i}"
+            );
+        }
+        let mut future = bytes.clone();
+        future[4] = 2;
+        assert!(matches!(
+            read(&future),
+            SnapshotError::UnsupportedVersion(2)
+        ));
+        assert!(matches!(read(b"PK\x03\x04"), SnapshotError::BadMagic));
+    }
+
+    #[test]
+    fn test_wrong_codec_is_a_decode_error() {
+        let bytes = snapshot(&DoublyLinkedList::from([1_u32, 2]));
+        let err = DoublyLinkedList::<u64>::read_snapshot(&bytes[..]).unwrap_err();
+        assert!(matches!(err, SnapshotError::Decode { index: 0 }));
+        assert_eq!(
+            err.to_string(),
+            "element 0 of the snapshot could not be decoded"
+        );
+        assert!(matches!(
+            DoublyLinkedList::<bool>::read_snapshot(&snapshot(&DoublyLinkedList::from([2_u8]))[..]),
+            Err(SnapshotError::Decode { index: 0 })
+        ));
+    }
+}
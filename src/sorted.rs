@@ -0,0 +1,568 @@
+use std::fmt;
+use std::iter::FusedIterator;
+use std::ops::{Bound, RangeBounds};
+
+use crate::{Cursor, CursorMut, DoublyLinkedList, IntoIter, Iter, NodeHandle};
+
+// Blocks never get shorter than this before being split, so small lists
+// are a single block walked from the head
+const MIN_BLOCK_LEN: usize = 16;
+
+/// The first element of a block of `len` consecutive elements.
+struct Fence<T> {
+    first: NodeHandle<T>,
+    len: usize,
+}
+
+/// The position of an element, as its `offset` into a block and its
+/// `index` in the list. Past the last element, `block` is the number of
+/// blocks.
+#[derive(Clone, Copy)]
+struct Location {
+    block: usize,
+    offset: usize,
+    index: usize,
+}
+
+/// A `DoublyLinkedList` that keeps its elements in ascending order.
+///
+/// Alongside the list, the elements are split into blocks of about √n
+/// consecutive nodes, and the first node of every block is kept as a
+/// [`NodeHandle`] in a sorted `Vec`. A lookup binary searches the blocks
+/// and then walks a single one, so finding, inserting and removing an
+/// element take O(√n) instead of a walk over the whole list.
+///
+/// Equal elements are kept in the order they were inserted.
+///
+/// ```
+/// use dll_rs::SortedList;
+///
+/// let mut timeline: SortedList<_> = [30, 10, 20].into_iter().collect();
+/// timeline.insert(25);
+/// assert_eq!(timeline.first(), Some(&10));
+/// assert_eq!(timeline.range(15..=25).copied().collect::<Vec<_>>(), [20, 25]);
+/// assert_eq!(timeline.lower_bound(&21).current(), Some(&25));
+/// assert_eq!(timeline.remove(&10), Some(10));
+/// assert!(!timeline.contains(&10));
+/// ```
+pub struct SortedList<T> {
+    list: DoublyLinkedList<T>,
+    fences: Vec<Fence<T>>, // Empty exactly when the list is empty
+}
+
+impl<T: Ord> SortedList<T> {
+    /// Creates a new, empty sorted list.
+    pub fn new() -> Self {
+        SortedList {
+            list: DoublyLinkedList::new(),
+            fences: Vec::new(),
+        }
+    }
+
+    /// Returns the number of elements in the list.
+    pub fn len(&self) -> usize {
+        self.list.len()
+    }
+
+    /// Returns true if the list contains no elements.
+    pub fn is_empty(&self) -> bool {
+        self.list.is_empty()
+    }
+
+    /// Returns the smallest element, or `None` if the list is empty.
+    pub fn first(&self) -> Option<&T> {
+        self.list.front()
+    }
+
+    /// Returns the largest element, or `None` if the list is empty.
+    pub fn last(&self) -> Option<&T> {
+        self.list.back()
+    }
+
+    /// Returns the underlying list, in ascending order.
+    pub fn as_list(&self) -> &DoublyLinkedList<T> {
+        &self.list
+    }
+
+    /// Returns an iterator over the elements in ascending order.
+    pub fn iter(&self) -> Iter<'_, T> {
+        self.list.iter()
+    }
+
+    /// Returns true if the list contains an element equal to `val`.
+    pub fn contains(&self, val: &T) -> bool {
+        self.lower_bound(val).current() == Some(val)
+    }
+
+    /// Inserts `val` after every element less than or equal to it.
+    pub fn insert(&mut self, val: T) {
+        let at = self.locate(|elem| elem <= &val);
+        if at.index == 0 {
+            let first = self.list.push_front_with_handle(val);
+            match self.fences.first_mut() {
+                Some(fence) => {
+                    fence.first = first;
+                    fence.len += 1;
+                }
+                None => self.fences.push(Fence { first, len: 1 }),
+            }
+            self.split_if_long(0);
+            return;
+        }
+        // Elements at the start of a block go to the end of the previous
+        // one, so the block keeps its fence
+        let block = if at.offset == 0 {
+            at.block - 1
+        } else {
+            at.block
+        };
+        if at.index == self.list.len() {
+            self.list.push_back(val);
+        } else {
+            self.cursor_mut_at(at).insert_before(val);
+        }
+        self.fences[block].len += 1;
+        self.split_if_long(block);
+    }
+
+    /// Removes the first element equal to `val` and returns it.
+    pub fn remove(&mut self, val: &T) -> Option<T> {
+        let at = self.locate(|elem| elem < val);
+        if self.cursor_at(at).current() != Some(val) {
+            return None;
+        }
+        Some(self.remove_at(at))
+    }
+
+    /// Removes the smallest element and returns it.
+    pub fn pop_first(&mut self) -> Option<T> {
+        let at = Location {
+            block: 0,
+            offset: 0,
+            index: 0,
+        };
+        (!self.is_empty()).then(|| self.remove_at(at))
+    }
+
+    /// Removes the largest element and returns it.
+    pub fn pop_last(&mut self) -> Option<T> {
+        let block = self.fences.len().checked_sub(1)?;
+        let offset = self.fences[block].len - 1;
+        let at = Location {
+            block,
+            offset,
+            index: self.list.len() - 1,
+        };
+        Some(self.remove_at(at))
+    }
+
+    /// Removes all elements.
+    pub fn clear(&mut self) {
+        self.fences.clear();
+        self.list.clear();
+    }
+
+    /// Returns a cursor pointing at the first element not less than `val`,
+    /// or at the ghost if there is none.
+    pub fn lower_bound(&self, val: &T) -> Cursor<'_, T> {
+        self.cursor_at(self.locate(|elem| elem < val))
+    }
+
+    /// Returns a cursor pointing at the first element greater than `val`,
+    /// or at the ghost if there is none.
+    /// ```
+    /// use dll_rs::SortedList;
+    ///
+    /// let list: SortedList<_> = [1, 2, 2, 3].into_iter().collect();
+    /// let mut cursor = list.upper_bound(&2);
+    /// assert_eq!(cursor.index(), Some(3));
+    /// cursor.move_prev();
+    /// assert_eq!(cursor.current(), Some(&2));
+    /// ```
+    pub fn upper_bound(&self, val: &T) -> Cursor<'_, T> {
+        self.cursor_at(self.locate(|elem| elem <= val))
+    }
+
+    /// Returns an iterator over the elements within `range`, in ascending
+    /// order.
+    pub fn range<R: RangeBounds<T>>(&self, range: R) -> Range<'_, T> {
+        let start = match range.start_bound() {
+            Bound::Included(start) => self.locate(|elem| elem < start),
+            Bound::Excluded(start) => self.locate(|elem| elem <= start),
+            Bound::Unbounded => self.locate(|_| false),
+        };
+        let end = match range.end_bound() {
+            Bound::Included(end) => self.locate(|elem| elem <= end),
+            Bound::Excluded(end) => self.locate(|elem| elem < end),
+            Bound::Unbounded => self.locate(|_| true),
+        };
+        let len = end.index.saturating_sub(start.index);
+        let mut back = self.cursor_at(end);
+        back.move_prev();
+        Range {
+            front: self.cursor_at(start),
+            back,
+            len,
+        }
+    }
+}
+
+impl<T> SortedList<T> {
+    /// Returns the index of the first element of `block`.
+    fn block_start(&self, block: usize) -> usize {
+        self.fences[..block].iter().map(|fence| fence.len).sum()
+    }
+
+    /// Finds the first element for which `before` returns false. `before`
+    /// must return true for a prefix of the list and false for the rest.
+    fn locate(&self, mut before: impl FnMut(&T) -> bool) -> Location {
+        let next_block = self
+            .fences
+            .partition_point(|fence| before(self.list.get_by_handle(&fence.first).unwrap()));
+        let Some(block) = next_block.checked_sub(1) else {
+            return Location {
+                block: 0,
+                offset: 0,
+                index: 0,
+            };
+        };
+        // Only the block before `next_block` can hold the element
+        let start = self.block_start(block);
+        let len = self.fences[block].len;
+        let mut cursor = self.cursor_at(Location {
+            block,
+            offset: 0,
+            index: start,
+        });
+        for offset in 1..len {
+            cursor.move_next();
+            if !before(cursor.current().unwrap()) {
+                return Location {
+                    block,
+                    offset,
+                    index: start + offset,
+                };
+            }
+        }
+        Location {
+            block: next_block,
+            offset: 0,
+            index: start + len,
+        }
+    }
+
+    /// Returns a cursor pointing at `at`.
+    fn cursor_at(&self, at: Location) -> Cursor<'_, T> {
+        let Some(fence) = self.fences.get(at.block) else {
+            let mut ghost = self.list.cursor_back();
+            if !self.list.is_empty() {
+                ghost.move_next();
+            }
+            return ghost;
+        };
+        let mut cursor = self
+            .list
+            .cursor_at_handle(&fence.first, at.index - at.offset)
+            .unwrap();
+        (0..at.offset).for_each(|_| cursor.move_next());
+        cursor
+    }
+
+    /// Returns a mutable cursor pointing at `at`, which must be an element.
+    fn cursor_mut_at(&mut self, at: Location) -> CursorMut<'_, T> {
+        let fence = &self.fences[at.block];
+        let mut cursor = self
+            .list
+            .cursor_mut_at_handle(&fence.first, at.index - at.offset)
+            .unwrap();
+        (0..at.offset).for_each(|_| cursor.move_next());
+        cursor
+    }
+
+    /// Removes the element at `at` and returns it.
+    fn remove_at(&mut self, at: Location) -> T {
+        let refence = at.offset == 0 && self.fences[at.block].len > 1;
+        let mut cursor = self.cursor_mut_at(at);
+        let val = cursor.remove_current().unwrap();
+        // The cursor moved on to the next element of the block
+        let next = if refence {
+            cursor.current_handle()
+        } else {
+            None
+        };
+        let fence = &mut self.fences[at.block];
+        fence.len -= 1;
+        if let Some(next) = next {
+            fence.first = next;
+        } else if fence.len == 0 {
+            self.fences.remove(at.block);
+        }
+        self.merge_if_short(at.block);
+        val
+    }
+
+    /// Returns how long blocks are meant to be, about √n.
+    fn block_len(&self) -> usize {
+        self.list.len().isqrt().max(MIN_BLOCK_LEN)
+    }
+
+    /// Splits `block` in half once it has grown to twice the block length.
+    fn split_if_long(&mut self, block: usize) {
+        let len = self.fences[block].len;
+        if len < 2 * self.block_len() {
+            return;
+        }
+        let start = self.block_start(block);
+        let half = len / 2;
+        let mut cursor = self.cursor_mut_at(Location {
+            block,
+            offset: half,
+            index: start + half,
+        });
+        let first = cursor.current_handle().unwrap();
+        self.fences[block].len = half;
+        self.fences.insert(
+            block + 1,
+            Fence {
+                first,
+                len: len - half,
+            },
+        );
+    }
+
+    /// Merges `block` into the previous one once it has shrunk to a
+    /// quarter of the block length, so the number of blocks stays O(√n).
+    fn merge_if_short(&mut self, block: usize) {
+        if block == 0 || block >= self.fences.len() {
+            return;
+        }
+        if self.fences[block].len * 4 <= self.block_len() {
+            let merged = self.fences.remove(block);
+            self.fences[block - 1].len += merged.len;
+        }
+    }
+}
+
+impl<T: Ord> Default for SortedList<T> {
+    /// Creates an empty `SortedList<T>`.
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for SortedList<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.list.iter()).finish()
+    }
+}
+
+impl<T: Ord> FromIterator<T> for SortedList<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut list = SortedList::new();
+        list.extend(iter);
+        list
+    }
+}
+
+impl<T: Ord> Extend<T> for SortedList<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        iter.into_iter().for_each(|val| self.insert(val));
+    }
+}
+
+impl<'a, T> IntoIterator for &'a SortedList<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.list.iter()
+    }
+}
+
+impl<T> IntoIterator for SortedList<T> {
+    type Item = T;
+    type IntoIter = IntoIter<T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.list.into_iter()
+    }
+}
+
+/// An iterator over a range of elements of a [`SortedList`], in ascending
+/// order.
+///
+/// This `struct` is created by [`SortedList::range`]. See its documentation
+/// for more.
+pub struct Range<'a, T> {
+    front: Cursor<'a, T>,
+    back: Cursor<'a, T>,
+    len: usize,
+}
+
+impl<T> Clone for Range<'_, T> {
+    fn clone(&self) -> Self {
+        Range {
+            front: self.front.clone(),
+            back: self.back.clone(),
+            len: self.len,
+        }
+    }
+}
+
+impl<'a, T> Iterator for Range<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.len == 0 {
+            return None;
+        }
+        self.len -= 1;
+        let val = self.front.current();
+        self.front.move_next();
+        val
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<T> DoubleEndedIterator for Range<'_, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.len == 0 {
+            return None;
+        }
+        self.len -= 1;
+        let val = self.back.current();
+        self.back.move_prev();
+        val
+    }
+}
+
+impl<T> ExactSizeIterator for Range<'_, T> {}
+
+impl<T> FusedIterator for Range<'_, T> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Checks that the blocks cover the list and start where they claim to.
+    fn check_fences<T: Ord + fmt::Debug>(list: &SortedList<T>) {
+        assert_eq!(list.block_start(list.fences.len()), list.len());
+        let mut index = 0;
+        for fence in &list.fences {
+            assert!(fence.len > 0);
+            let first = list.list.get_by_handle(&fence.first).unwrap();
+            assert_eq!(list.list.get(index), Some(first));
+            index += fence.len;
+        }
+        assert!(list.list.is_sorted());
+    }
+
+    #[test]
+    fn test_matches_sorted_vec() {
+        let mut list = SortedList::new();
+        let mut model = Vec::new();
+        let mut rng = fastrand::Rng::with_seed(7);
+        for step in 0..3000 {
+            let val = rng.u32(..500);
+            if step % 3 == 2 {
+                let removed = list.remove(&val);
+                match model.binary_search(&val) {
+                    Ok(i) => assert_eq!(removed, Some(model.remove(i))),
+                    Err(_) => assert_eq!(removed, None),
+                }
+            } else {
+                list.insert(val);
+                let i = model.partition_point(|&x| x <= val);
+                model.insert(i, val);
+            }
+            assert_eq!(list.contains(&val), model.binary_search(&val).is_ok());
+        }
+        check_fences(&list);
+        assert!(list.fences.len() > 1);
+        assert_eq!(list.iter().copied().collect::<Vec<_>>(), model);
+
+        let range: Vec<_> = list.range(100..200).copied().collect();
+        let expected: Vec<_> = model
+            .iter()
+            .copied()
+            .filter(|x| (100..200).contains(x))
+            .collect();
+        assert_eq!(range, expected);
+        assert_eq!(list.range(100..200).len(), expected.len());
+        assert!(list.range(100..200).rev().eq(expected.iter().rev()));
+        assert_eq!(list.range(..).len(), model.len());
+
+        for probe in [0, 250, 499, 500] {
+            let lower = list.lower_bound(&probe);
+            let i = model.partition_point(|&x| x < probe);
+            assert_eq!(lower.index().unwrap_or(model.len()), i);
+            assert_eq!(lower.current(), model.get(i));
+            let upper = list.upper_bound(&probe);
+            assert_eq!(
+                upper.current(),
+                model.get(model.partition_point(|&x| x <= probe))
+            );
+        }
+
+        while let Some(first) = list.pop_first() {
+            assert_eq!(first, model.remove(0));
+            if let Some(last) = list.pop_last() {
+                assert_eq!(last, model.pop().unwrap());
+            }
+            if list.len() % 97 == 0 {
+                check_fences(&list);
+            }
+        }
+        assert!(model.is_empty());
+        assert!(list.fences.is_empty());
+    }
+
+    #[test]
+    fn test_equal_elements_keep_insertion_order() {
+        let mut list = SortedList::new();
+        for (i, key) in [2, 1, 2, 1, 2].into_iter().enumerate() {
+            list.insert(Keyed(key, i));
+        }
+        let order: Vec<_> = list.iter().map(|k| k.1).collect();
+        assert_eq!(order, [1, 3, 0, 2, 4]);
+        assert_eq!(list.remove(&Keyed(2, 99)).map(|k| k.1), Some(0));
+        assert_eq!(
+            list.range(Keyed(2, 0)..).map(|k| k.1).collect::<Vec<_>>(),
+            [2, 4]
+        );
+        assert_eq!(list.first().map(|k| k.1), Some(1));
+        assert_eq!(list.last().map(|k| k.1), Some(4));
+        assert!(list.range(Keyed(3, 0)..Keyed(1, 0)).next().is_none());
+
+        list.clear();
+        assert!(list.is_empty());
+        assert!(list.lower_bound(&Keyed(0, 0)).current().is_none());
+    }
+
+    /// Ordered by its first field only.
+    #[derive(Debug)]
+    struct Keyed(i32, usize);
+
+    impl PartialEq for Keyed {
+        fn eq(&self, other: &Self) -> bool {
+            self.0 == other.0
+        }
+    }
+
+    impl Eq for Keyed {}
+
+    impl PartialOrd for Keyed {
+        fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+            Some(self.cmp(other))
+        }
+    }
+
+    impl Ord for Keyed {
+        fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+            self.0.cmp(&other.0)
+        }
+    }
+}